@@ -1,13 +1,60 @@
 use anyhow::Result;
-use clap::{Parser, Subcommand};
+use clap::{Args, CommandFactory, FromArgMatches, Parser, Subcommand, ValueEnum};
 use colored::*;
 use serde_json::Value;
+use std::collections::BTreeMap;
+use std::path::PathBuf;
 use std::process::{Command, Stdio};
+use std::sync::OnceLock;
 
 #[derive(Parser)]
 #[command(name = "kdbg")]
 #[command(about = "Kubernetes Pod Debugger - Fast kubectl wrapper", long_about = None)]
 struct Cli {
+    /// Narrate each step in plain English as it runs
+    #[arg(long, global = true)]
+    explain: bool,
+    
+    /// Show what mutating commands would do without changing anything
+    #[arg(long, global = true)]
+    dry_run: bool,
+    
+    /// Send mutating commands to the API server with --dry-run=server: validated and run through admission webhooks, never persisted
+    #[arg(long, global = true, conflicts_with = "dry_run")]
+    server_dry_run: bool,
+    
+    /// Kubernetes context to use (default: $KDBG_CONTEXT, the config file, or kubectl's current context)
+    #[arg(long, global = true)]
+    context: Option<String>,
+    
+    /// Path to the kubeconfig file kubectl should use
+    #[arg(long, global = true)]
+    kubeconfig: Option<String>,
+    
+    /// Namespace, before or after the subcommand (repeatable, or a glob like 'team-*')
+    #[arg(short, long, global = true)]
+    namespace: Vec<String>,
+    
+    /// How failures are reported on stderr: human text or one JSON object for tooling
+    #[arg(long, global = true, value_enum, default_value = "human")]
+    error_format: ErrorFormat,
+    
+    /// Disable colored output (NO_COLOR is honored as well)
+    #[arg(long, global = true)]
+    no_color: bool,
+    
+    /// Skip the kubectl checks run before each command (for tight loops)
+    #[arg(long, global = true)]
+    no_preflight: bool,
+    
+    /// Color palette (default: theme in the config file, or default)
+    #[arg(long, global = true, value_enum)]
+    theme: Option<ThemeName>,
+    
+    /// Let -n match part of a namespace name when no namespace is called exactly that (or fuzzy_namespaces = true in the config)
+    #[arg(long, global = true)]
+    fuzzy_ns: bool,
+    
     #[command(subcommand)]
     command: Commands,
 }
@@ -15,79 +62,225 @@ struct Cli {
 #[derive(Subcommand)]
 enum Commands {
     /// List all pods
-    List {
-        /// Namespace (default: all)
+    List(ListArgs),
+    
+    /// Get pod logs
+    Logs(LogsArgs),
+    
+    /// Execute command in pod
+    Exec {
+        /// Pod name (or partial match)
+        pod: String,
+        
+        /// Namespace (repeatable, or a glob like 'team-*')
         #[arg(short, long)]
-        namespace: Option<String>,
+        namespace: Vec<String>,
+        
+        /// Command to run (default: /bin/sh)
+        #[arg(short, long)]
+        command: Option<String>,
+        
+        /// Command and arguments after --, passed through without a shell
+        #[arg(last = true, conflicts_with = "command", value_name = "ARGS")]
+        args: Vec<String>,
         
-        /// Show more details
+        /// Working directory to run the command in
         #[arg(short, long)]
-        verbose: bool,
+        workdir: Option<String>,
+        
+        /// Environment variable to set (KEY=VAL, repeatable)
+        #[arg(short, long)]
+        env: Vec<String>,
+        
+        /// Run the command in every container of the pod, one after another
+        #[arg(long)]
+        all_containers: bool,
+        
+        /// If the pod is replaced mid-run (e.g. a rolling update), re-run the command in its successor
+        #[arg(long, conflicts_with = "all_containers")]
+        retry_on_restart: bool,
+        
+        /// Run a command saved under [exec_presets] in the config file (through sh -c)
+        #[arg(long, value_name = "NAME", conflicts_with_all = ["command", "args"])]
+        preset: Option<String>,
     },
     
-    /// Get pod logs
-    Logs {
-        /// Pod name (or partial match)
-        pod: String,
+    /// Describe pod
+    Describe {
+        /// Pod name (or partial match), optional with --selector
+        #[arg(required_unless_present = "selector")]
+        pod: Option<String>,
         
-        /// Namespace
+        /// Namespace (repeatable, or a glob like 'team-*')
         #[arg(short, long)]
-        namespace: Option<String>,
+        namespace: Vec<String>,
+        
+        /// Show spec differences against another pod instead
+        #[arg(long, value_name = "POD", conflicts_with = "selector")]
+        diff: Option<String>,
         
-        /// Follow logs
+        /// Describe every pod matching this label selector
+        #[arg(short = 'l', long, conflicts_with = "pod")]
+        selector: Option<String>,
+        
+        /// Show the combined output in the pager (with --selector)
+        #[arg(long, requires = "selector")]
+        page: bool,
+        
+        /// Don't ask for confirmation when many pods match
         #[arg(short, long)]
-        follow: bool,
+        yes: bool,
+        
+        /// Refresh a summary of the pod every N seconds (default 2) until it is Ready
+        #[arg(short, long, value_name = "SECONDS", num_args = 0..=1, default_missing_value = "2", conflicts_with_all = ["selector", "diff"])]
+        watch: Option<u64>,
+        
+        /// Only show the pod's events (same as `kdbg events`), skipping the rest of describe
+        #[arg(long, conflicts_with_all = ["selector", "diff", "watch"])]
+        events_only: bool,
         
-        /// Number of lines
-        #[arg(long, default_value = "100")]
-        tail: u32,
+        /// Print kdbg's compact summary with the pod's conditions (same as `kdbg info`)
+        #[arg(long, conflicts_with_all = ["selector", "diff", "watch", "events_only"])]
+        summary: bool,
+        
+        /// Extra kubectl flags after --, appended to the kubectl command kdbg runs
+        #[arg(last = true, value_name = "KUBECTL_ARGS")]
+        kubectl_args: Vec<String>,
     },
     
-    /// Execute command in pod
-    Exec {
+    /// Show the most-needed facts about a pod at a glance
+    Info {
         /// Pod name (or partial match)
         pod: String,
         
-        /// Namespace
+        /// Namespace (repeatable, or a glob like 'team-*')
         #[arg(short, long)]
-        namespace: Option<String>,
+        namespace: Vec<String>,
         
-        /// Command to run (default: /bin/sh)
-        #[arg(short, long, default_value = "/bin/sh")]
-        command: String,
+        /// Output format
+        #[arg(short, long, value_enum, default_value = "text")]
+        output: InfoFormat,
+        
+        /// Also check DNS and connectivity from inside the pod
+        #[arg(long)]
+        net: bool,
+        
+        /// Host or host:port to resolve and connect to with --net (repeatable)
+        #[arg(long, value_name = "HOST[:PORT]", requires = "net")]
+        probe: Vec<String>,
     },
     
-    /// Describe pod
-    Describe {
+    /// Print a single field of a pod via a kubectl JSONPath expression
+    Get {
         /// Pod name (or partial match)
         pod: String,
         
-        /// Namespace
+        /// Namespace (repeatable, or a glob like 'team-*')
         #[arg(short, long)]
-        namespace: Option<String>,
+        namespace: Vec<String>,
+        
+        /// JSONPath expression, e.g. '{.status.podIP}'
+        #[arg(long)]
+        jsonpath: String,
     },
     
     /// Show pod resource usage
     Top {
-        /// Namespace
+        /// Pod name (or partial match), with --compare or --containers
+        pod: Option<String>,
+        
+        /// Namespace (repeatable, or a glob like 'team-*')
         #[arg(short, long)]
-        namespace: Option<String>,
+        namespace: Vec<String>,
+        
+        /// Compare each container's usage with its requests and limits
+        #[arg(long, requires = "pod", conflicts_with = "samples")]
+        compare: bool,
+        
+        /// Take N samples and report min/avg/max per pod
+        #[arg(long)]
+        samples: Option<u32>,
+        
+        /// Seconds between samples
+        #[arg(long, default_value = "5")]
+        interval: u64,
+        
+        /// Only pods matching this label selector
+        #[arg(short = 'l', long, conflicts_with = "compare")]
+        selector: Option<String>,
+        
+        /// Add a footer with the pod count and summed CPU and memory (with --samples, of the averages)
+        #[arg(long, visible_alias = "summary", conflicts_with = "compare")]
+        aggregate: bool,
+        
+        /// With --aggregate, print only the total line
+        #[arg(long, requires = "aggregate")]
+        total_only: bool,
+        
+        /// Break each pod's usage down by container, heaviest first
+        #[arg(long, conflicts_with_all = ["compare", "samples"])]
+        containers: bool,
+        
+        /// Output format; json prints numeric records sorted by namespace and pod
+        #[arg(short, long, value_enum, default_value = "text", conflicts_with_all = ["compare", "samples", "aggregate"])]
+        output: InfoFormat,
+        
+        /// Refresh every N seconds with a sparkline of each pod's recent CPU and memory (q to quit)
+        #[arg(short, long, value_name = "SECONDS", num_args = 0..=1, default_missing_value = "2", conflicts_with_all = ["compare", "samples", "containers", "output"])]
+        watch: Option<u64>,
     },
     
     /// Port forward to pod
     Forward {
+        /// [POD] LOCAL_PORT POD_PORT, or [POD] LOCAL:POD (pod omitted with --selector)
+        #[arg(required = true, num_args = 1..=3, value_name = "ARGS")]
+        target: Vec<String>,
+        
+        /// Namespace (repeatable, or a glob like 'team-*')
+        #[arg(short, long)]
+        namespace: Vec<String>,
+        
+        /// Label selector; forwards to a ready matching pod
+        #[arg(short = 'l', long)]
+        selector: Option<String>,
+        
+        /// Reconnect when the forward dies
+        #[arg(short, long)]
+        reconnect: bool,
+        
+        /// Warn (or reconnect with --reconnect) after no traffic for this long, e.g. 10m
+        #[arg(long, value_parser = parse_duration)]
+        idle_timeout: Option<std::time::Duration>,
+        
+        /// Spread connections across all ready pods matching --selector through a local proxy
+        #[arg(long, requires = "selector", conflicts_with_all = ["reconnect", "idle_timeout"])]
+        round_robin: bool,
+        
+        /// Extra kubectl flags after --, appended to the kubectl command kdbg runs
+        #[arg(last = true, value_name = "KUBECTL_ARGS")]
+        kubectl_args: Vec<String>,
+    },
+    
+    /// Attach to a container's main process, waiting for it to be running
+    Attach {
         /// Pod name (or partial match)
         pod: String,
         
-        /// Local port
-        local_port: u16,
-        
-        /// Pod port
-        pod_port: u16,
+        /// Namespace (repeatable, or a glob like 'team-*')
+        #[arg(short, long)]
+        namespace: Vec<String>,
         
-        /// Namespace
+        /// Container (default: the pod's default container)
         #[arg(short, long)]
-        namespace: Option<String>,
+        container: Option<String>,
+        
+        /// How long to wait for the container to be running, e.g. 30s or 2m
+        #[arg(long, value_parser = parse_duration, default_value = "1m")]
+        timeout: std::time::Duration,
+        
+        /// Extra kubectl flags after --, appended to the kubectl command kdbg runs
+        #[arg(last = true, value_name = "KUBECTL_ARGS")]
+        kubectl_args: Vec<String>,
     },
     
     /// Open interactive shell in pod
@@ -95,9 +288,13 @@ enum Commands {
         /// Pod name (or partial match)
         pod: String,
         
-        /// Namespace
+        /// Namespace (repeatable, or a glob like 'team-*')
         #[arg(short, long)]
-        namespace: Option<String>,
+        namespace: Vec<String>,
+        
+        /// Extra kubectl flags after --, appended to the kubectl command kdbg runs
+        #[arg(last = true, value_name = "KUBECTL_ARGS")]
+        kubectl_args: Vec<String>,
     },
     
     /// Create debug pod and shell into it
@@ -106,440 +303,8345 @@ enum Commands {
         #[arg(short, long, default_value = "busybox")]
         image: String,
         
-        /// Namespace
-        #[arg(short, long, default_value = "default")]
-        namespace: String,
+        /// Namespace (default: "default")
+        #[arg(short, long)]
+        namespace: Option<String>,
+        
+        /// Attach an ephemeral debug container to this pod instead
+        #[arg(short, long, value_name = "POD")]
+        ephemeral: Option<String>,
+        
+        /// kubectl debug profile (legacy, general, baseline, restricted, netadmin, sysadmin)
+        #[arg(short, long, requires = "ephemeral")]
+        profile: Option<String>,
+        
+        /// Copy env and envFrom from this pod's first container
+        #[arg(long, value_name = "POD", conflicts_with = "ephemeral")]
+        copy_env_from: Option<String>,
+        
+        /// Debug a copy of this pod with the container's command replaced by sleep
+        #[arg(long, value_name = "POD", conflicts_with_all = ["ephemeral", "copy_env_from", "image", "image_pull_policy"])]
+        copy: Option<String>,
+        
+        /// Container to take over in the copy (default: first container)
+        #[arg(short, long, requires = "copy")]
+        container: Option<String>,
+        
+        /// Keep the debug pod after the shell exits so it can be re-entered
+        #[arg(short, long, conflicts_with = "ephemeral")]
+        keep: bool,
+        
+        /// Image pull policy for the debug container
+        #[arg(long, value_enum, ignore_case = true)]
+        image_pull_policy: Option<PullPolicy>,
+    },
+    
+    /// Wait for a pod to reach a condition (wraps kubectl wait)
+    Wait {
+        /// Pod name (or partial match), optional with --selector
+        #[arg(required_unless_present = "selector")]
+        pod: Option<String>,
+        
+        /// Namespace (repeatable, or a glob like 'team-*')
+        #[arg(short, long)]
+        namespace: Vec<String>,
+        
+        /// Wait on all pods matching this label selector instead
+        #[arg(short = 'l', long, conflicts_with = "pod")]
+        selector: Option<String>,
+        
+        /// Condition to wait for: condition=NAME[=VALUE], delete, or jsonpath=...
+        #[arg(long = "for", value_name = "CONDITION", default_value = "condition=Ready")]
+        for_condition: String,
+        
+        /// Give up after this long (kubectl duration, e.g. 60s, 5m)
+        #[arg(long, default_value = "60s")]
+        timeout: String,
+        
+        /// Print the pods' Warning events (scheduling, image pulls, probes) as they happen while waiting
+        #[arg(long)]
+        show_events: bool,
+        
+        /// Extra kubectl flags after --, appended to the kubectl command kdbg runs
+        #[arg(last = true, value_name = "KUBECTL_ARGS")]
+        kubectl_args: Vec<String>,
+    },
+    
+    /// Watch a pod and save the previous container's logs whenever it restarts
+    Watch {
+        /// Pod name (or partial match)
+        pod: String,
+        
+        /// Namespace (repeatable, or a glob like 'team-*')
+        #[arg(short, long)]
+        namespace: Vec<String>,
+        
+        /// Seconds between polls
+        #[arg(long, default_value = "5")]
+        interval: u64,
+        
+        /// Directory to write captured logs to
+        #[arg(short, long, default_value = ".")]
+        dir: PathBuf,
+        
+        /// Also snapshot the pod's events on each restart, before they age out
+        #[arg(long)]
+        previous_events: bool,
     },
     
     /// Restart pod (delete and let it recreate)
     Restart {
+        /// Pod name (or partial match), optional with --selector
+        #[arg(required_unless_present = "selector")]
+        pod: Option<String>,
+        
+        /// Restart every pod matching this label selector
+        #[arg(short = 'l', long, conflicts_with_all = ["pod", "rollout"])]
+        selector: Option<String>,
+        
+        /// Don't ask for confirmation (the pods affected are still listed)
+        #[arg(short, long)]
+        yes: bool,
+        
+        /// Namespace (repeatable, or a glob like 'team-*')
+        #[arg(short, long)]
+        namespace: Vec<String>,
+        
+        /// Rollout-restart the pod's Deployment/StatefulSet/DaemonSet instead
+        #[arg(long, conflicts_with_all = ["grace_period", "wait"])]
+        rollout: bool,
+        
+        /// Allow matching pods in any namespace when no namespace is set
+        #[arg(long)]
+        confirm_all_namespaces: bool,
+        
+        #[command(flatten)]
+        delete: DeleteOptions,
+        
+        /// Wait for the replacement pod to become ready, printing Warning events as they happen
+        #[arg(long, conflicts_with_all = ["selector", "rollout"])]
+        show_events: bool,
+        
+        /// How long --show-events waits for the replacement (e.g. 90s, 5m)
+        #[arg(long, value_parser = parse_duration, default_value = "2m", requires = "show_events")]
+        timeout: std::time::Duration,
+        
+        /// Extra kubectl flags after --, appended to the kubectl command kdbg runs
+        #[arg(last = true, value_name = "KUBECTL_ARGS")]
+        kubectl_args: Vec<String>,
+    },
+    
+    /// Create a standalone copy of a pod, detached from its controller, to poke at its exact spec
+    Recreate {
         /// Pod name (or partial match)
         pod: String,
         
-        /// Namespace
+        /// Namespace (repeatable, or a glob like 'team-*')
         #[arg(short, long)]
-        namespace: Option<String>,
+        namespace: Vec<String>,
+        
+        /// Don't ask for confirmation
+        #[arg(short, long)]
+        yes: bool,
+        
+        /// Extra kubectl flags after --, appended to the kubectl command kdbg runs
+        #[arg(last = true, value_name = "KUBECTL_ARGS")]
+        kubectl_args: Vec<String>,
+    },
+    
+    /// Scale the Deployment or StatefulSet managing a pod, showing the pods affected first
+    Scale {
+        /// Pod name (or partial match), or the controller as KIND/NAME (e.g. deployment/api)
+        target: String,
+        
+        /// Desired number of replicas
+        #[arg(long)]
+        replicas: u32,
+        
+        /// Namespace (repeatable, or a glob like 'team-*'; a single one for KIND/NAME)
+        #[arg(short, long)]
+        namespace: Vec<String>,
+        
+        /// Don't ask for confirmation (the pods affected are still listed)
+        #[arg(short, long)]
+        yes: bool,
+        
+        /// Extra kubectl flags after --, appended to the kubectl command kdbg runs
+        #[arg(last = true, value_name = "KUBECTL_ARGS")]
+        kubectl_args: Vec<String>,
+    },
+    
+    /// Delete a pod, or every pod matching a selector
+    Delete {
+        /// Pod name (or partial match), optional with --selector
+        #[arg(required_unless_present = "selector")]
+        pod: Option<String>,
+        
+        /// Delete every pod matching this label selector
+        #[arg(short = 'l', long, conflicts_with = "pod")]
+        selector: Option<String>,
+        
+        /// Don't ask for confirmation before deleting selector matches
+        #[arg(short, long)]
+        yes: bool,
+        
+        /// Namespace (repeatable, or a glob like 'team-*')
+        #[arg(short, long)]
+        namespace: Vec<String>,
+        
+        /// Allow matching pods in any namespace when no namespace is set
+        #[arg(long)]
+        confirm_all_namespaces: bool,
+        
+        #[command(flatten)]
+        delete: DeleteOptions,
+        
+        /// Extra kubectl flags after --, appended to the kubectl command kdbg runs
+        #[arg(last = true, value_name = "KUBECTL_ARGS")]
+        kubectl_args: Vec<String>,
     },
     
     /// Show pod events
     Events {
-        /// Pod name (or partial match)
-        pod: String,
+        /// Pod name (or partial match), optional with --all-namespaces
+        #[arg(required_unless_present = "all_namespaces")]
+        pod: Option<String>,
         
-        /// Namespace
+        /// Namespace (repeatable, or a glob like 'team-*')
         #[arg(short, long)]
-        namespace: Option<String>,
+        namespace: Vec<String>,
+        
+        /// Show events across all namespaces
+        #[arg(short = 'A', long, conflicts_with = "namespace")]
+        all_namespaces: bool,
+        
+        /// Sort events by this field
+        #[arg(long, value_enum, default_value = "lastTimestamp")]
+        sort_by: EventSort,
+        
+        /// Reverse the sort order (e.g. newest or most frequent first)
+        #[arg(short, long)]
+        reverse: bool,
+    },
+    
+    /// Copy files to or from a pod (use POD:PATH for the pod side)
+    Cp {
+        /// Source (local path or POD:PATH)
+        src: String,
+        
+        /// Destination (local path or POD:PATH)
+        dest: String,
+        
+        /// Namespace (repeatable, or a glob like 'team-*')
+        #[arg(short, long)]
+        namespace: Vec<String>,
+        
+        /// Container (required for multi-container pods)
+        #[arg(short, long)]
+        container: Option<String>,
+        
+        /// Extra kubectl flags after --, appended to the kubectl command kdbg runs
+        #[arg(last = true, value_name = "KUBECTL_ARGS")]
+        kubectl_args: Vec<String>,
+    },
+    
+    /// List kubeconfig contexts, or switch kubectl to the one matching NAME
+    Ctx {
+        /// Context name (or partial match) to switch to
+        name: Option<String>,
+    },
+    
+    /// List namespaces, or set the current context's default namespace to the one matching NAME
+    Ns {
+        /// Namespace name (or partial match) to switch to
+        name: Option<String>,
     },
+    
+    /// Show recently run kdbg commands (enable with `[history] enabled = true` in the config)
+    History {
+        /// Number of entries to show
+        #[arg(long, default_value = "20")]
+        limit: usize,
+    },
+    
+    /// Print a shell completion script, or install it with --install
+    Completions {
+        /// Shell to generate for (default with --install: detected from $SHELL)
+        #[arg(value_enum, required_unless_present = "install")]
+        shell: Option<Shell>,
+        
+        /// Write the script to the shell's completion directory
+        #[arg(long)]
+        install: bool,
+    },
+    
+    /// Print namespace names for shell completion
+    #[command(name = "__complete_namespaces", hide = true)]
+    CompleteNamespaces,
+    
+    /// Print context names for shell completion
+    #[command(name = "__complete_contexts", hide = true)]
+    CompleteContexts,
 }
 
-fn main() -> Result<()> {
-    let cli = Cli::parse();
+#[derive(Args)]
+struct DeleteOptions {
+    /// Seconds the pod gets to shut down (kubectl delete --grace-period)
+    #[arg(long, value_name = "SECONDS")]
+    grace_period: Option<u32>,
     
-    match cli.command {
-        Commands::List { namespace, verbose } => list_pods(namespace, verbose)?,
-        Commands::Logs { pod, namespace, follow, tail } => show_logs(&pod, namespace, follow, tail)?,
-        Commands::Exec { pod, namespace, command } => exec_pod(&pod, namespace, &command)?,
-        Commands::Describe { pod, namespace } => describe_pod(&pod, namespace)?,
-        Commands::Top { namespace } => show_top(namespace)?,
-        Commands::Forward { pod, local_port, pod_port, namespace } => {
-            port_forward(&pod, local_port, pod_port, namespace)?
-        }
-        Commands::Shell { pod, namespace } => shell_pod(&pod, namespace)?,
-        Commands::Debug { image, namespace } => debug_pod(&image, &namespace)?,
-        Commands::Restart { pod, namespace } => restart_pod(&pod, namespace)?,
-        Commands::Events { pod, namespace } => show_events(&pod, namespace)?,
-    }
-    
-    Ok(())
+    /// Return only once the pod is gone, finalizers included
+    #[arg(long)]
+    wait: bool,
 }
 
-fn list_pods(namespace: Option<String>, verbose: bool) -> Result<()> {
-    let mut args = vec!["get", "pods"];
+#[derive(Args)]
+struct ListArgs {
+    /// Resource type to list instead of pods, e.g. nodes, deployments, pv (rendered by kubectl)
+    resource: Option<String>,
     
-    let ns_str;
-    if let Some(ns) = &namespace {
-        ns_str = ns.clone();
-        args.extend(&["-n", &ns_str]);
-    } else {
-        args.push("--all-namespaces");
-    }
+    /// Namespace (default: all; repeatable, or a glob like 'team-*')
+    #[arg(short, long)]
+    namespace: Vec<String>,
     
-    args.push("-o");
-    args.push("json");
+    /// Show more details
+    #[arg(short, long)]
+    verbose: bool,
     
-    let output = Command::new("kubectl")
-        .args(&args)
-        .output()?;
+    /// Group pods by node, namespace or owning controller
+    #[arg(short, long, value_enum)]
+    group_by: Option<GroupBy>,
     
-    if !output.status.success() {
-        eprintln!("{} kubectl command failed", "[ERROR]".red());
-        return Ok(());
-    }
+    /// Print kubectl's own table instead of kdbg's
+    #[arg(long, conflicts_with_all = ["verbose", "group_by"])]
+    raw: bool,
     
-    let json: Value = serde_json::from_slice(&output.stdout)?;
-    let empty_vec = vec![];
-    let pods = json["items"].as_array().unwrap_or(&empty_vec);
+    /// Label selector (e.g. app=api)
+    #[arg(short = 'l', long)]
+    selector: Option<String>,
     
-    println!("{}", "Pods:".cyan().bold());
-    println!("{}", "-".repeat(100));
+    /// Only show pods in this phase (e.g. Running, Pending)
+    #[arg(short, long)]
+    status: Option<String>,
     
-    if verbose {
-        println!("{:<40} {:<15} {:<10} {:<15} {:<20}", 
-            "NAME", "NAMESPACE", "STATUS", "RESTARTS", "AGE");
-        println!("{}", "-".repeat(100));
-    } else {
-        println!("{:<40} {:<15} {:<10}", "NAME", "NAMESPACE", "STATUS");
-        println!("{}", "-".repeat(100));
-    }
+    /// Print only the number of matching pods (with -v, a per-phase breakdown)
+    #[arg(short, long, conflicts_with = "group_by")]
+    count: bool,
     
-    for pod in pods {
-        let name = pod["metadata"]["name"].as_str().unwrap_or("unknown");
-        let ns = pod["metadata"]["namespace"].as_str().unwrap_or("default");
-        let phase = pod["status"]["phase"].as_str().unwrap_or("Unknown");
-        
-        let status_colored = match phase {
-            "Running" => phase.green(),
-            "Pending" => phase.yellow(),
-            "Failed" => phase.red(),
-            "Succeeded" => phase.blue(),
-            _ => phase.normal(),
-        };
-        
-        if verbose {
-            let restarts = pod["status"]["containerStatuses"]
-                .as_array()
-                .and_then(|cs| cs.first())
-                .and_then(|c| c["restartCount"].as_u64())
-                .unwrap_or(0);
-            
-            let age = pod["metadata"]["creationTimestamp"]
-                .as_str()
-                .map(|ts| calculate_age(ts))
-                .unwrap_or("unknown".to_string());
-            
-            println!("{:<40} {:<15} {:<10} {:<15} {:<20}", 
-                name.cyan(), ns.bright_black(), status_colored, restarts, age);
-        } else {
-            println!("{:<40} {:<15} {:<10}", name.cyan(), ns.bright_black(), status_colored);
-        }
-    }
+    /// Output format: table, wide, json, or kubectl's yaml, name, jsonpath=..., go-template=...
+    #[arg(short, long)]
+    output: Option<String>,
     
-    println!("\nTotal: {} pods", pods.len());
+    /// Highlight pods with at least this many restarts in red (default: restart_crit in config, or 5)
+    #[arg(long)]
+    restart_threshold: Option<u64>,
     
-    Ok(())
-}
-
-fn find_pod(pod_pattern: &str, namespace: Option<String>) -> Result<(String, String)> {
-    let mut args = vec!["get", "pods"];
+    /// Show each pod's controller (Deployment, StatefulSet, ...); implied by -o wide
+    #[arg(long)]
+    show_owner: bool,
     
-    let ns_str;
-    if let Some(ns) = &namespace {
-        ns_str = ns.clone();
-        args.extend(&["-n", &ns_str]);
-    } else {
-        args.push("--all-namespaces");
-    }
+    /// Show each pod's QoS class, colored by eviction risk; implied by -v
+    #[arg(long)]
+    show_qos: bool,
     
-    args.extend(&["-o", "json"]);
+    /// Add an IMAGES column with every container's image, comma-separated (sidecars included)
+    #[arg(long)]
+    show_images: bool,
     
-    let output = Command::new("kubectl")
-        .args(&args)
-        .output()?;
+    /// Like --show-images, plus init container images (marked init:)
+    #[arg(long)]
+    show_all_images: bool,
     
-    let json: Value = serde_json::from_slice(&output.stdout)?;
-    let empty_vec = vec![];
-    let pods = json["items"].as_array().unwrap_or(&empty_vec);
+    /// Only show pods managed by this controller (NAME or KIND/NAME)
+    #[arg(long, value_name = "NAME", conflicts_with = "raw")]
+    owner: Option<String>,
     
-    let matches: Vec<_> = pods.iter()
-        .filter(|pod| {
-            let name = pod["metadata"]["name"].as_str().unwrap_or("");
-            name.contains(pod_pattern)
-        })
-        .collect();
+    /// Only show pods created, started or restarted within this window, e.g. 5m or 1h
+    #[arg(long, value_name = "DURATION", value_parser = parse_duration, conflicts_with = "raw")]
+    changed_since: Option<std::time::Duration>,
     
-    if matches.is_empty() {
-        anyhow::bail!("No pods found matching '{}'", pod_pattern);
-    }
+    /// Refresh the table every N seconds (q to quit)
+    #[arg(short, long, value_name = "SECONDS", num_args = 0..=1, default_missing_value = "2", conflicts_with_all = ["raw", "count"])]
+    watch: Option<u64>,
     
-    if matches.len() > 1 {
-        println!("{} Multiple pods found:", "[INFO]".yellow());
-        for pod in &matches {
-            let name = pod["metadata"]["name"].as_str().unwrap_or("unknown");
-            let ns = pod["metadata"]["namespace"].as_str().unwrap_or("default");
-            println!("  - {} (namespace: {})", name.cyan(), ns.bright_black());
-        }
-        anyhow::bail!("Please be more specific");
-    }
+    /// With --watch, print each refresh as one JSON object per pod; pods added, changed or removed since the last refresh carry an `_event` field
+    #[arg(long, requires = "watch", conflicts_with_all = ["output", "group_by"])]
+    json_lines: bool,
     
-    let pod = matches[0];
-    let name = pod["metadata"]["name"].as_str().unwrap_or("unknown").to_string();
-    let ns = pod["metadata"]["namespace"].as_str().unwrap_or("default").to_string();
+    /// Load a custom-columns spec from a file (one column per line or comma-separated)
+    #[arg(long, value_name = "PATH", conflicts_with_all = ["output", "raw", "watch"])]
+    columns_file: Option<PathBuf>,
+    
+    /// Use a custom-columns spec saved under [columns] in the config file
+    #[arg(long, value_name = "NAME", conflicts_with_all = ["output", "raw", "watch", "columns_file"])]
+    preset: Option<String>,
     
-    Ok((name, ns))
+    /// Leave out the "Pods:" heading (printed to stderr)
+    #[arg(long, conflicts_with = "raw")]
+    no_title: bool,
+    
+    /// Leave out the "Total: N pods" footer (printed to stderr)
+    #[arg(long, conflicts_with = "raw")]
+    no_total: bool,
+    
+    /// Only unhealthy pods, each with the reasons why; exits non-zero if there are any
+    #[arg(long, conflicts_with_all = ["raw", "output", "count", "group_by", "watch", "columns_file", "preset"])]
+    problems: bool,
 }
 
-fn show_logs(pod_pattern: &str, namespace: Option<String>, follow: bool, tail: u32) -> Result<()> {
-    let (pod_name, ns) = find_pod(pod_pattern, namespace)?;
+#[derive(Args)]
+struct LogsArgs {
+    /// Pod name (or partial match), optional with --job
+    #[arg(required_unless_present = "job")]
+    pod: Option<String>,
     
-    println!("{} Logs for pod: {} (namespace: {})", 
-        "[INFO]".cyan(), pod_name.bold(), ns.bright_black());
-    println!("{}", "-".repeat(100));
+    /// Logs of every pod of this Job, finished ones included, each line prefixed with its pod
+    #[arg(long, value_name = "NAME", conflicts_with_all = ["pod", "init", "ordinal", "follow_restarts", "all_previous", "resume"])]
+    job: Option<String>,
     
-    let tail_str = tail.to_string();
-    let mut args = vec!["logs", &pod_name, "-n", &ns, "--tail", &tail_str];
+    /// Namespace (repeatable, or a glob like 'team-*')
+    #[arg(short, long)]
+    namespace: Vec<String>,
     
-    if follow {
-        args.push("-f");
-    }
+    /// Container (default: the pod's default container); `init` picks the init container, `*` all of them
+    #[arg(short, long)]
+    container: Option<String>,
     
-    let status = Command::new("kubectl")
-        .args(&args)
-        .status()?;
+    /// Same as `-c init`
+    #[arg(long, conflicts_with = "container")]
+    init: bool,
     
-    if !status.success() {
-        anyhow::bail!("Failed to get logs");
-    }
+    /// StatefulSet pod ordinal: `kdbg logs web --ordinal 2` is web-2 (same as web#2)
+    #[arg(long, value_name = "N")]
+    ordinal: Option<u32>,
     
-    Ok(())
-}
-
-fn exec_pod(pod_pattern: &str, namespace: Option<String>, command: &str) -> Result<()> {
-    let (pod_name, ns) = find_pod(pod_pattern, namespace)?;
+    /// Interleave every container's logs, each line prefixed with its container (same as `-c '*'`)
+    #[arg(long, conflicts_with_all = ["container", "init"])]
+    all_containers: bool,
     
-    println!("{} Executing in pod: {} (namespace: {})", 
-        "[INFO]".cyan(), pod_name.bold(), ns.bright_black());
-    println!("{} Command: {}", "[INFO]".cyan(), command.yellow());
-    println!("{}", "-".repeat(100));
+    /// Follow logs
+    #[arg(short, long)]
+    follow: bool,
     
-    let status = Command::new("kubectl")
-        .args(&["exec", "-it", &pod_name, "-n", &ns, "--", command])
-        .status()?;
+    /// Keep following when the pod is replaced (deploy, restart, eviction); implies --follow
+    #[arg(long)]
+    follow_restarts: bool,
     
-    if !status.success() {
-        anyhow::bail!("Failed to exec into pod");
-    }
+    /// Number of lines
+    #[arg(long, default_value = "100")]
+    tail: u32,
     
-    Ok(())
-}
-
-fn describe_pod(pod_pattern: &str, namespace: Option<String>) -> Result<()> {
-    let (pod_name, ns) = find_pod(pod_pattern, namespace)?;
+    /// Stop after this many bytes of log, cut server-side (applies together with --tail)
+    #[arg(long, value_name = "BYTES", value_parser = clap::value_parser!(u64).range(1..))]
+    limit_bytes: Option<u64>,
     
-    println!("{} Describing pod: {} (namespace: {})", 
-        "[INFO]".cyan(), pod_name.bold(), ns.bright_black());
-    println!("{}", "-".repeat(100));
+    /// Reformat JSON log lines as `timestamp level message`
+    #[arg(long)]
+    pretty_json: bool,
     
-    let status = Command::new("kubectl")
-        .args(&["describe", "pod", &pod_name, "-n", &ns])
-        .status()?;
+    /// Open the logs in a searchable pager (/ to search, n/N to jump, q to quit)
+    #[arg(short, long, conflicts_with_all = ["follow", "follow_restarts"])]
+    interactive: bool,
     
-    if !status.success() {
-        anyhow::bail!("Failed to describe pod");
-    }
+    /// Skip the container status line printed before the logs
+    #[arg(short, long)]
+    quiet: bool,
     
-    Ok(())
+    /// Print the full logs of the current and the previous container instance, labeled with restart details
+    #[arg(long, conflicts_with_all = ["follow", "follow_restarts", "interactive"])]
+    all_previous: bool,
+    
+    /// While following, keep the last --tail-lines lines in this file
+    #[arg(long, value_name = "PATH")]
+    tail_file: Option<PathBuf>,
+    
+    /// Lines kept in --tail-file
+    #[arg(long, default_value = "1000", requires = "tail_file")]
+    tail_lines: usize,
+    
+    /// Continue after the last line a previous --resume run printed for this pod and container (instead of --tail)
+    #[arg(long, conflicts_with_all = ["interactive", "all_previous", "follow_restarts", "all_containers"])]
+    resume: bool,
+    
+    /// Collapse repeated consecutive lines into one `line (xN)`, updated in place on a terminal
+    #[arg(long, conflicts_with_all = ["interactive", "all_previous"])]
+    dedup: bool,
+    
+    /// With --dedup, also fold a line into a match among the last N distinct lines, not only the previous one
+    #[arg(long, value_name = "N", requires = "dedup", value_parser = clap::value_parser!(u64).range(1..))]
+    dedup_window: Option<u64>,
+    
+    /// Extra kubectl flags after --, appended to the kubectl logs command
+    #[arg(last = true, value_name = "KUBECTL_ARGS")]
+    kubectl_args: Vec<String>,
 }
 
-fn show_top(namespace: Option<String>) -> Result<()> {
-    let mut args = vec!["top", "pods"];
+enum ListOutput {
+    Table,
+    Wide,
+    Json,
+    // From -o custom-columns=NAME:.metadata.name,..., --columns-file or --preset
+    CustomColumns(Vec<CustomColumn>),
+    // Formats kubectl renders itself; kdbg only forwards the spec
+    Passthrough(String),
+}
+
+impl ListOutput {
+    // --preset and --columns-file are saved custom-columns specs
+    fn from_args(list: &ListArgs) -> Result<Self> {
+        if let Some(name) = &list.preset {
+            let spec = config().get(&format!("columns.{}", name)).ok_or_else(|| {
+                anyhow::anyhow!("No column preset '{}'; define it under [columns] in the config file", name)
+            })?;
+            return Ok(ListOutput::CustomColumns(parse_columns(&spec)?));
+        }
+        
+        if let Some(path) = &list.columns_file {
+            let spec = std::fs::read_to_string(path)
+                .map_err(|e| anyhow::anyhow!("Failed to read {}: {}", path.display(), e))?;
+            return Ok(ListOutput::CustomColumns(parse_columns(&spec)?));
+        }
+        
+        ListOutput::parse(list.output.as_deref())
+    }
     
-    let ns_str;
-    if let Some(ns) = &namespace {
-        ns_str = ns.clone();
-        args.extend(&["-n", &ns_str]);
-    } else {
-        args.push("--all-namespaces");
+    fn parse(spec: Option<&str>) -> Result<Self> {
+        const PASSTHROUGH: &[&str] = &[
+            "go-template=", "go-template-file=", "jsonpath=", "jsonpath-file=",
+            "custom-columns-file=", "template=", "templatefile=",
+        ];
+        
+        match spec {
+            None | Some("table") => Ok(ListOutput::Table),
+            Some("wide") => Ok(ListOutput::Wide),
+            Some("json") => Ok(ListOutput::Json),
+            Some(spec @ ("yaml" | "name")) => Ok(ListOutput::Passthrough(spec.to_string())),
+            Some(spec) if spec.starts_with("custom-columns=") => {
+                Ok(ListOutput::CustomColumns(parse_columns(&spec["custom-columns=".len()..])?))
+            }
+            Some(spec) if PASSTHROUGH.iter().any(|prefix| spec.starts_with(prefix)) => {
+                Ok(ListOutput::Passthrough(spec.to_string()))
+            }
+            Some(spec) => anyhow::bail!(
+                "Output format '{}' is not supported; use table, wide, json, custom-columns=..., \
+                 or a format kubectl renders: yaml, name, jsonpath=..., go-template=...",
+                spec
+            ),
+        }
+    }
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum GroupBy {
+    Node,
+    Namespace,
+    Owner,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum PullPolicy {
+    #[value(name = "Always")]
+    Always,
+    #[value(name = "IfNotPresent")]
+    IfNotPresent,
+    #[value(name = "Never")]
+    Never,
+}
+
+impl PullPolicy {
+    fn as_str(self) -> &'static str {
+        match self {
+            PullPolicy::Always => "Always",
+            PullPolicy::IfNotPresent => "IfNotPresent",
+            PullPolicy::Never => "Never",
+        }
+    }
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum InfoFormat {
+    Text,
+    Json,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum ThemeName {
+    Default,
+    HighContrast,
+    Light,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum ErrorFormat {
+    Human,
+    Json,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Shell {
+    Bash,
+    Zsh,
+    Fish,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum EventSort {
+    #[value(name = "lastTimestamp")]
+    LastTimestamp,
+    #[value(name = "firstTimestamp")]
+    FirstTimestamp,
+    Count,
+    Type,
+}
+
+// kubectl debug profiles and the kubectl minor version that introduced them
+const DEBUG_PROFILES: &[(&str, u32)] = &[
+    ("legacy", 26),
+    ("general", 26),
+    ("baseline", 26),
+    ("restricted", 26),
+    ("netadmin", 27),
+    ("sysadmin", 27),
+];
+
+const DEFAULT_RESTART_THRESHOLD: u64 = 5;
+const DEFAULT_RESTART_WARN: u64 = 3;
+
+// Both thresholds are inclusive, like list's --restart-threshold, which
+// overrides crit: counts at or above `warn` get the warning color, at or above
+// `crit` the danger color. A single restart is routine (a node drain, an
+// OOM during a spike), so warn starts at 3. restart_crit in the config was
+// called restart_threshold before, which is still read
+#[derive(Clone, Copy)]
+struct RestartThresholds {
+    warn: u64,
+    crit: u64,
+}
+
+impl RestartThresholds {
+    fn load(crit_override: Option<u64>) -> RestartThresholds {
+        let config = config();
+        RestartThresholds {
+            warn: config.get_u64("restart_warn").unwrap_or(DEFAULT_RESTART_WARN),
+            crit: crit_override
+                .or_else(|| config.get_u64("restart_crit"))
+                .or_else(|| config.get_u64("restart_threshold"))
+                .unwrap_or(DEFAULT_RESTART_THRESHOLD),
+        }
     }
     
-    println!("{}", "Pod Resource Usage:".cyan().bold());
-    println!("{}", "-".repeat(100));
+    fn colored(&self, restarts: u64, text: &str) -> ColoredString {
+        if restarts >= self.crit {
+            text.danger().bold()
+        } else if restarts >= self.warn {
+            text.warning()
+        } else {
+            text.normal()
+        }
+    }
+}
+
+// Batch describe asks before going past this many pods
+const DESCRIBE_CONFIRM_LIMIT: usize = 10;
+
+// How often forward --round-robin re-checks which pods are ready
+const ROUND_ROBIN_REFRESH_SECS: u64 = 5;
+
+// How often exec --retry-on-restart follows a replaced pod before giving up
+const EXEC_RETRY_LIMIT: u32 = 3;
+
+// How often restart/wait --show-events polls for new Warning events
+const EVENT_WATCH_INTERVAL_SECS: u64 = 2;
+
+// How many kubectl logs runs in a row logs --follow-restarts lets fail before giving up
+const FOLLOW_FAILURE_LIMIT: u32 = 3;
+
+// How long completion candidates are reused before asking kubectl again
+const COMPLETION_CACHE_SECS: u64 = 10;
+
+fn main() -> Result<()> {
+    use std::io::IsTerminal;
     
-    let status = Command::new("kubectl")
-        .args(&args)
-        .status()?;
+    // Redirected output is data: keep it free of ANSI codes, even when
+    // CLICOLOR_FORCE is inherited from the environment
+    if !std::io::stdout().is_terminal() {
+        colored::control::set_override(false);
+    }
     
-    if !status.success() {
-        eprintln!("{} Failed to get resource usage (metrics-server may not be installed)", 
-            "[WARN]".yellow());
+    let matches = Cli::command().get_matches();
+    let cli = Cli::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
+    if cli.no_color {
+        colored::control::set_override(false);
     }
+    THEME.get_or_init(|| Theme::load(cli.theme));
+    EXPLAIN.get_or_init(|| cli.explain);
+    DRY_RUN.get_or_init(|| cli.dry_run);
+    SERVER_DRY_RUN.get_or_init(|| cli.server_dry_run);
+    CONTEXT.get_or_init(|| cli.context.or_else(|| env_or_config("KDBG_CONTEXT", "context")));
+    KUBECONFIG.get_or_init(|| cli.kubeconfig);
+    record_history(&matches);
+    let mut command = cli.command;
+    apply_env_defaults(&mut command, cli.namespace);
+    KUBECTL_ARGS.get_or_init(|| take_kubectl_args(&mut command));
     
-    Ok(())
-}
-
-fn port_forward(pod_pattern: &str, local_port: u16, pod_port: u16, namespace: Option<String>) -> Result<()> {
-    let (pod_name, ns) = find_pod(pod_pattern, namespace)?;
+    let fuzzy_ns = cli.fuzzy_ns || config().get_bool("fuzzy_namespaces").unwrap_or(false);
     
-    println!("{} Port forwarding: localhost:{} -> {}:{} (namespace: {})", 
-        "[INFO]".cyan(), local_port, pod_name.bold(), pod_port, ns.bright_black());
-    println!("{} Press Ctrl+C to stop", "[INFO]".yellow());
-    println!("{}", "-".repeat(100));
+    let result = if cli.no_preflight { Ok(()) } else { preflight(&command) }
+        .and_then(|()| if fuzzy_ns { resolve_fuzzy_namespaces(&mut command) } else { Ok(()) })
+        .and_then(|()| run(command));
     
-    let status = Command::new("kubectl")
-        .args(&[
-            "port-forward",
-            &pod_name,
-            &format!("{}:{}", local_port, pod_port),
-            "-n",
-            &ns,
-        ])
-        .status()?;
+    if let (Err(e), ErrorFormat::Json) = (&result, cli.error_format) {
+        eprintln!("{}", error_json(e));
+        std::process::exit(1);
+    }
     
-    if !status.success() {
-        anyhow::bail!("Port forwarding failed");
+    result
+}
+
+fn run(command: Commands) -> Result<()> {
+    match command {
+        Commands::List(args) => {
+            if let Some(resource) = args.resource.as_deref().filter(|r| !is_pod_resource(r)) {
+                list_resources(&args, resource)?
+            } else if args.raw {
+                list_pods_raw(&args)?
+            } else {
+                list_pods(&args)?
+            }
+        }
+        Commands::Logs(args) => show_logs(&args)?,
+        Commands::Exec { pod, namespace, command, args, workdir, env, all_containers, retry_on_restart, preset } => {
+            let command: Vec<String> = match preset {
+                Some(name) => exec_preset(&name)?,
+                None => command.into_iter().chain(args).collect(),
+            };
+            exec_pod(&pod, namespace_arg(namespace), &command, workdir.as_deref(), &env, all_containers, retry_on_restart)?
+        }
+        Commands::Describe { pod, namespace, diff, selector, page, yes, watch, events_only, summary, .. } => match (pod, selector, diff) {
+            (_, Some(sel), _) => describe_selector(&sel, namespace_arg(namespace), page, yes)?,
+            (Some(pod), None, Some(other)) => diff_pods(&pod, &other, namespace_arg(namespace))?,
+            (Some(pod), None, None) if watch.is_some() => {
+                watch_describe(&pod, namespace_arg(namespace), watch.unwrap_or(2))?
+            }
+            (Some(pod), None, None) if events_only => {
+                show_events(&pod, namespace_arg(namespace), EventSort::LastTimestamp, false)?
+            }
+            (Some(pod), None, None) if summary => show_info(&pod, namespace_arg(namespace), InfoFormat::Text, None)?,
+            (Some(pod), None, None) => describe_pod(&pod, namespace_arg(namespace))?,
+            (None, None, _) => anyhow::bail!("Specify a pod or --selector"),
+        },
+        Commands::Info { pod, namespace, output, net, probe } => {
+            let probes = if net { Some(probe.as_slice()) } else { None };
+            show_info(&pod, namespace_arg(namespace), output, probes)?
+        }
+        Commands::Get { pod, namespace, jsonpath } => get_field(&pod, namespace_arg(namespace), &jsonpath)?,
+        Commands::Top { pod, namespace, compare, samples, interval, selector, aggregate, total_only, containers, output, watch } => match pod {
+            Some(pod) if compare => compare_top(&pod, namespace_arg(namespace))?,
+            None if watch.is_some() => watch_top(namespace_arg(namespace), selector.as_deref(), watch.unwrap_or(2))?,
+            _ if matches!(output, InfoFormat::Json) => {
+                print_top_json(pod.as_deref(), namespace_arg(namespace), selector.as_deref(), containers)?
+            }
+            _ if containers => {
+                show_top_containers(pod.as_deref(), namespace_arg(namespace), selector.as_deref(), aggregate, total_only)?
+            }
+            Some(_) => anyhow::bail!("A pod name is only used with --compare or --containers"),
+            None => {
+                let ns = namespace_arg(namespace);
+                match samples {
+                    Some(n) => show_top_samples(ns, selector.as_deref(), n, interval, aggregate, total_only)?,
+                    None => show_top(ns, selector.as_deref(), aggregate, total_only)?,
+                }
+            }
+        },
+        Commands::Forward { target, namespace, selector, reconnect, idle_timeout, round_robin, .. } => {
+            let (pod, local_port, pod_port) = parse_forward_target(&target, selector.is_some())?;
+            if let (true, Some(sel)) = (round_robin, &selector) {
+                return round_robin_forward(sel, local_port, pod_port, namespace_arg(namespace));
+            }
+            port_forward(pod.as_deref(), selector.as_deref(), local_port, pod_port, namespace_arg(namespace), reconnect, idle_timeout)?
+        }
+        Commands::Shell { pod, namespace, .. } => shell_pod(&pod, namespace_arg(namespace))?,
+        Commands::Attach { pod, namespace, container, timeout, .. } => {
+            attach_pod(&pod, namespace_arg(namespace), container.as_deref(), timeout)?
+        }
+        Commands::Debug { image, namespace, ephemeral, profile, copy_env_from, copy, container, keep, image_pull_policy } => {
+            let namespace = namespace.unwrap_or_else(|| "default".to_string());
+            if let Some(pod) = copy {
+                return debug_copy(&pod, &namespace, container.as_deref(), keep);
+            }
+            let pull_policy = image_pull_policy.map(PullPolicy::as_str);
+            debug_pod(&image, &namespace, ephemeral.as_deref(), profile.as_deref(), copy_env_from.as_deref(), keep, pull_policy)?
+        }
+        Commands::Wait { pod, namespace, selector, for_condition, timeout, show_events, .. } => {
+            wait_pods(pod.as_deref(), selector.as_deref(), namespace_arg(namespace), &for_condition, &timeout, show_events)?
+        }
+        Commands::Watch { pod, namespace, interval, dir, previous_events } => {
+            watch_pod(&pod, namespace_arg(namespace), interval, &dir, previous_events)?
+        }
+        Commands::Restart { pod, selector, yes, namespace, rollout, confirm_all_namespaces, delete, show_events, timeout, .. } => match (pod, selector) {
+            (_, Some(sel)) => delete_selector(&sel, namespace_arg(namespace), confirm_all_namespaces, yes, &delete, "restart")?,
+            (Some(pod), None) => {
+                let recovery = show_events.then_some(timeout);
+                restart_pod(&pod, namespace_arg(namespace), rollout, confirm_all_namespaces, yes, &delete, recovery)?
+            }
+            (None, None) => anyhow::bail!("Specify a pod or --selector"),
+        },
+        Commands::Recreate { pod, namespace, yes, .. } => recreate_pod(&pod, namespace_arg(namespace), yes)?,
+        Commands::Scale { target, replicas, namespace, yes, .. } => scale(&target, replicas, namespace_arg(namespace), yes)?,
+        Commands::Delete { pod, selector, yes, namespace, confirm_all_namespaces, delete, .. } => match (pod, selector) {
+            (_, Some(sel)) => delete_selector(&sel, namespace_arg(namespace), confirm_all_namespaces, yes, &delete, "delete")?,
+            (Some(pod), None) => delete_single_pod(&pod, namespace_arg(namespace), confirm_all_namespaces, &delete)?,
+            (None, None) => anyhow::bail!("Specify a pod or --selector"),
+        },
+        Commands::Events { pod, namespace, all_namespaces, sort_by, reverse } => {
+            if all_namespaces {
+                show_events_all_namespaces(pod.as_deref(), sort_by, reverse)?
+            } else if let Some(pod) = pod {
+                show_events(&pod, namespace_arg(namespace), sort_by, reverse)?
+            }
+        }
+        Commands::Cp { src, dest, namespace, container, .. } => {
+            copy_files(&src, &dest, namespace_arg(namespace), container.as_deref())?
+        }
+        Commands::Ctx { name } => switch_context(name.as_deref())?,
+        Commands::Ns { name } => switch_namespace(name.as_deref())?,
+        Commands::History { limit } => show_history(limit)?,
+        Commands::Completions { shell, install } => {
+            if install {
+                install_completions(shell)?
+            } else if let Some(shell) = shell {
+                print!("{}", completion_script(shell))
+            }
+        }
+        Commands::CompleteNamespaces => {
+            complete_values("namespaces", &["get", "namespaces", "-o", "name"])?
+        }
+        Commands::CompleteContexts => {
+            complete_values("contexts", &["config", "get-contexts", "-o", "name"])?
+        }
     }
     
     Ok(())
 }
 
-fn shell_pod(pod_pattern: &str, namespace: Option<String>) -> Result<()> {
-    let (pod_name, ns) = find_pod(pod_pattern, namespace)?;
+// Only the kubectl call that does the command's actual work gets the extra
+// flags; the lookups around it (get pods -o json, ...) would reject most of them
+fn take_kubectl_args(command: &mut Commands) -> Vec<String> {
+    match command {
+        Commands::Logs(args) => std::mem::take(&mut args.kubectl_args),
+        Commands::Describe { kubectl_args, .. }
+        | Commands::Forward { kubectl_args, .. }
+        | Commands::Attach { kubectl_args, .. }
+        | Commands::Shell { kubectl_args, .. }
+        | Commands::Wait { kubectl_args, .. }
+        | Commands::Restart { kubectl_args, .. }
+        | Commands::Delete { kubectl_args, .. }
+        | Commands::Scale { kubectl_args, .. }
+        | Commands::Recreate { kubectl_args, .. }
+        | Commands::Cp { kubectl_args, .. } => std::mem::take(kubectl_args),
+        _ => Vec::new(),
+    }
+}
+
+/// Fill in an omitted --namespace. Precedence: the --namespace flag (after
+/// or before the subcommand), then $KDBG_NAMESPACE, then `namespace` in the
+/// config file, then kubectl's own default (the current context's namespace).
+fn apply_env_defaults(command: &mut Commands, global_namespace: Vec<String>) {
+    if let Commands::Debug { namespace, .. } = command {
+        if namespace.is_none() {
+            *namespace = global_namespace.into_iter().next()
+                .or_else(|| env_or_config("KDBG_NAMESPACE", "namespace"));
+        }
+        return;
+    }
+    let Some(namespace) = namespace_values(command) else {
+        return;
+    };
     
-    println!("{} Opening shell in pod: {} (namespace: {})", 
-        "[INFO]".cyan(), pod_name.bold(), ns.bright_black());
-    println!("{}", "-".repeat(100));
+    if namespace.is_empty() {
+        namespace.extend(global_namespace);
+    }
+    if namespace.is_empty() {
+        namespace.extend(env_or_config("KDBG_NAMESPACE", "namespace"));
+    }
+}
+
+// The -n values of the commands with a repeatable namespace option
+fn namespace_values(command: &mut Commands) -> Option<&mut Vec<String>> {
+    let namespace = match command {
+        Commands::List(args) => &mut args.namespace,
+        Commands::Logs(args) => &mut args.namespace,
+        Commands::Exec { namespace, .. }
+        | Commands::Describe { namespace, .. }
+        | Commands::Get { namespace, .. }
+        | Commands::Info { namespace, .. }
+        | Commands::Top { namespace, .. }
+        | Commands::Forward { namespace, .. }
+        | Commands::Shell { namespace, .. }
+        | Commands::Attach { namespace, .. }
+        | Commands::Wait { namespace, .. }
+        | Commands::Watch { namespace, .. }
+        | Commands::Restart { namespace, .. }
+        | Commands::Delete { namespace, .. }
+        | Commands::Scale { namespace, .. }
+        | Commands::Recreate { namespace, .. }
+        | Commands::Cp { namespace, .. } => namespace,
+        Commands::Events { namespace, all_namespaces: false, .. } => namespace,
+        Commands::Events { .. }
+        | Commands::Debug { .. }
+        | Commands::Ctx { .. }
+        | Commands::Ns { .. }
+        | Commands::History { .. }
+        | Commands::Completions { .. }
+        | Commands::CompleteNamespaces
+        | Commands::CompleteContexts => return None,
+    };
     
-    // Try bash first, fall back to sh
-    let shells = ["/bin/bash", "/bin/sh"];
+    Some(namespace)
+}
+
+// --fuzzy-ns: a namespace that doesn't exist is matched like a pod name, so
+// `-n prod` finds `production`; an exact name always wins, and globs and
+// namespace sets are left to expand_namespaces
+fn resolve_fuzzy_namespaces(command: &mut Commands) -> Result<()> {
+    let mut values: Vec<&mut String> = match command {
+        Commands::Debug { namespace, .. } => namespace.iter_mut().collect(),
+        _ => namespace_values(command).into_iter().flatten().collect(),
+    };
+    values.retain(|ns| !is_namespace_set(ns));
+    if values.is_empty() {
+        return Ok(());
+    }
     
-    for (i, shell) in shells.iter().enumerate() {
-        let mut cmd = Command::new("kubectl");
-        cmd.args(&["exec", "-it", &pod_name, "-n", &ns, "--", shell]);
-        
-        // Inherit stdin/stdout/stderr for interactive shell
-        cmd.stdin(Stdio::inherit())
-           .stdout(Stdio::inherit())
-           .stderr(Stdio::null()); // Suppress error messages when trying shells
-        
-        let status = cmd.status()?;
-        
-        if status.success() {
+    // Without permission to list namespaces, -n is used as given
+    let namespaces = match list_namespaces() {
+        Ok(namespaces) => namespaces,
+        Err(e) => {
+            eprintln!("{} Can't match namespaces fuzzily: {}", "[WARN]".warning(), e);
             return Ok(());
         }
-        
-        // If bash failed, try sh (last attempt with stderr visible)
-        if i == shells.len() - 1 {
-            let mut cmd = Command::new("kubectl");
-            cmd.args(&["exec", "-it", &pod_name, "-n", &ns, "--", shell]);
-            cmd.stdin(Stdio::inherit())
-               .stdout(Stdio::inherit())
-               .stderr(Stdio::inherit());
-            
-            let status = cmd.status()?;
-            if status.success() {
-                return Ok(());
-            }
+    };
+    
+    for ns in values {
+        let matched = match_name("namespaces", &namespaces, ns)?;
+        if matched != ns.as_str() {
+            eprintln!("{} Namespace '{}' matched {}", "[INFO]".accent(), ns, matched.bold());
+            *ns = matched.to_string();
         }
     }
     
-    anyhow::bail!("Failed to open shell (tried bash and sh)")
+    Ok(())
 }
 
-fn debug_pod(image: &str, namespace: &str) -> Result<()> {
-    use std::time::{SystemTime, UNIX_EPOCH};
-    
-    let timestamp = SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .unwrap()
-        .as_secs();
+fn list_namespaces() -> Result<Vec<String>> {
+    let output = run_kubectl(&["get", "namespaces", "-o", "name"])?;
+    if !output.status.success() {
+        anyhow::bail!("Failed to list namespaces: {}", String::from_utf8_lossy(&output.stderr).trim());
+    }
     
-    let pod_name = format!("debug-{}", timestamp);
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(|line| line.trim_start_matches("namespace/").to_string())
+        .collect())
+}
+
+// Repeated --namespace flags travel as one comma-separated namespace set,
+// which fetch_pods and friends expand (see expand_namespaces)
+fn namespace_arg(values: Vec<String>) -> Option<String> {
+    (!values.is_empty()).then(|| values.join(","))
+}
+
+fn is_namespace_set(namespace: &str) -> bool {
+    namespace.contains([',', '*', '?'])
+}
+
+// Resolves a namespace set to concrete namespaces, listing them only when a
+// glob needs matching. A plain namespace is returned unchanged.
+fn expand_namespaces(namespace: &str) -> Result<Vec<String>> {
+    expand_namespaces_from(namespace, list_namespaces)
+}
+
+fn expand_namespaces_from(namespace: &str, list: impl FnOnce() -> Result<Vec<String>>) -> Result<Vec<String>> {
+    let patterns: Vec<&str> = namespace.split(',').map(str::trim).filter(|p| !p.is_empty()).collect();
     
-    println!("{} Creating debug pod: {} (image: {}, namespace: {})", 
-        "[INFO]".cyan(), pod_name.bold(), image.yellow(), namespace.bright_black());
-    println!("{} Pod will be deleted when you exit the shell", "[INFO]".yellow());
-    println!("{}", "-".repeat(100));
+    if !patterns.iter().any(|p| p.contains(['*', '?'])) {
+        return Ok(patterns.into_iter().map(String::from).collect());
+    }
     
-    // Create pod
-    let output = Command::new("kubectl")
-        .args(&[
-            "run",
-            &pod_name,
-            "--image", image,
-            "-n", namespace,
-            "--restart=Never",
-            "--rm",
-            "-it",
-            "--",
-            "/bin/sh",
-        ])
-        .stdin(Stdio::inherit())
-        .stdout(Stdio::inherit())
-        .stderr(Stdio::inherit())
-        .status()?;
+    let matched: Vec<String> = list()?
+        .into_iter()
+        .filter(|name| patterns.iter().any(|p| glob_match(p, name)))
+        .collect();
     
-    if !output.success() {
-        anyhow::bail!("Failed to create debug pod");
+    if matched.is_empty() {
+        anyhow::bail!("No namespaces match '{}'", namespace);
     }
     
-    Ok(())
+    Ok(matched)
 }
 
-fn restart_pod(pod_pattern: &str, namespace: Option<String>) -> Result<()> {
-    let (pod_name, ns) = find_pod(pod_pattern, namespace)?;
+fn single_namespace(namespace: &Option<String>) -> Result<()> {
+    match namespace {
+        Some(ns) if is_namespace_set(ns) => anyhow::bail!("This command needs a single namespace, got '{}'", ns),
+        _ => Ok(()),
+    }
+}
+
+// Shell-style glob: '*' matches any run of characters, '?' exactly one
+fn glob_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+    let (mut p, mut t) = (0, 0);
+    let mut backtrack: Option<(usize, usize)> = None;
     
-    println!("{} Restarting pod: {} (namespace: {})", 
-        "[INFO]".cyan(), pod_name.bold(), ns.bright_black());
-    println!("{} This will delete the pod and let the controller recreate it", 
-        "[INFO]".yellow());
-    println!("{}", "-".repeat(100));
+    while t < text.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                backtrack = Some((p, t));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == text[t] => {
+                p += 1;
+                t += 1;
+            }
+            _ => match backtrack {
+                Some((star_p, star_t)) => {
+                    p = star_p + 1;
+                    t = star_t + 1;
+                    backtrack = Some((star_p, star_t + 1));
+                }
+                None => return false,
+            },
+        }
+    }
     
-    let status = Command::new("kubectl")
-        .args(&["delete", "pod", &pod_name, "-n", &ns])
-        .status()?;
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+/// Context passed to every kubectl call: $KDBG_CONTEXT, then `context` in the
+/// config file, otherwise kubectl's current context.
+static CONTEXT: OnceLock<Option<String>> = OnceLock::new();
+static KUBECONFIG: OnceLock<Option<String>> = OnceLock::new();
+
+fn default_context() -> Option<&'static str> {
+    CONTEXT.get_or_init(|| env_or_config("KDBG_CONTEXT", "context")).as_deref()
+}
+
+// Precedence below the command-line flags: environment, then the nearest
+// .kdbg project file, then the user's config file
+fn env_or_config(var: &str, key: &str) -> Option<String> {
+    std::env::var(var)
+        .ok()
+        .filter(|value| !value.is_empty())
+        .or_else(|| project_config().get(key))
+        .or_else(|| config().get(key))
+}
+
+// Every kubectl invocation is built here, so context and --explain apply everywhere
+fn kubectl(args: &[&str]) -> Command {
+    let mut cmd = Command::new("kubectl");
+    if let Some(kubeconfig) = KUBECONFIG.get().and_then(Option::as_deref) {
+        cmd.args(["--kubeconfig", kubeconfig]);
+    }
+    // `kubectl config` reads and edits the kubeconfig itself, where --context
+    // would only get in the way
+    if let Some(context) = default_context()
+        && args.first() != Some(&"config")
+    {
+        cmd.args(["--context", context]);
+    }
     
-    if !status.success() {
-        anyhow::bail!("Failed to delete pod");
+    explain(|| format!("{} {}", describe_kubectl(args), format!("(kubectl {})", args.join(" ")).dimmed()));
+    
+    cmd.args(args);
+    cmd
+}
+
+static EXPLAIN: OnceLock<bool> = OnceLock::new();
+static DRY_RUN: OnceLock<bool> = OnceLock::new();
+static SERVER_DRY_RUN: OnceLock<bool> = OnceLock::new();
+static KUBECTL_ARGS: OnceLock<Vec<String>> = OnceLock::new();
+
+// Flags given after `--`, spliced in after the ones kdbg generates
+fn extra_kubectl_args<'a>() -> impl Iterator<Item = &'a str> {
+    KUBECTL_ARGS.get().into_iter().flatten().map(|arg| -> &'a str { arg })
+}
+
+fn is_dry_run() -> bool {
+    DRY_RUN.get().copied().unwrap_or(false)
+}
+
+// Unlike --dry-run, the command does reach the API server, which validates it
+// and asks admission webhooks; kubectl prints the response, tagged "(server dry run)"
+fn is_server_dry_run() -> bool {
+    SERVER_DRY_RUN.get().copied().unwrap_or(false)
+}
+
+fn server_dry_run_arg() -> Option<&'static str> {
+    is_server_dry_run().then_some("--dry-run=server")
+}
+
+// Stands in for the success message when nothing was persisted
+fn reported_server_dry_run() -> bool {
+    if is_server_dry_run() {
+        println!("{} The API server accepted the change; nothing was persisted", "[DRY RUN]".warning());
+    }
+    is_server_dry_run()
+}
+
+// Prints a --explain narration line; goes to stderr so piped output stays clean
+fn explain(message: impl FnOnce() -> String) {
+    if EXPLAIN.get().copied().unwrap_or(false) {
+        eprintln!("{} {}", "[EXPLAIN]".magenta(), message());
     }
+}
+
+fn describe_kubectl(args: &[&str]) -> String {
+    let scope = if args.contains(&"--all-namespaces") {
+        "in all namespaces".to_string()
+    } else {
+        match args.iter().position(|a| *a == "-n").and_then(|i| args.get(i + 1)) {
+            Some(ns) => format!("in namespace {}", ns),
+            None => "in the current namespace".to_string(),
+        }
+    };
     
-    println!("{} Pod deleted. Waiting for recreation...", "[SUCCESS]".green());
+    // Resource/pod names always precede flags at kdbg's call sites
+    let positional: Vec<&str> = args.iter().skip(1).copied().filter(|a| !a.starts_with('-')).collect();
+    let first = positional.first().copied().unwrap_or("");
+    let second = positional.get(1).copied().unwrap_or("");
     
-    Ok(())
+    match args.first().copied().unwrap_or("") {
+        "get" if first == "pods" => format!("Listing pods {}...", scope),
+        "get" => format!("Fetching {} {}...", first, scope),
+        "logs" => format!("Reading logs of pod {}...", first),
+        "exec" => format!("Running a command inside pod {}...", first),
+        "describe" => format!("Asking the API server to describe {} {}...", first, second),
+        "top" => format!("Reading live CPU and memory usage from metrics-server {}...", scope),
+        "port-forward" => format!("Opening a tunnel from this machine to pod {}...", first),
+        "rollout" => format!("Triggering a rolling restart of {}...", second),
+        "scale" => format!("Changing the replica count of {}...", first),
+        "apply" => format!("Creating the objects from the manifest {}...", scope),
+        "delete" => format!("Deleting {} {}...", first, second),
+        "debug" => format!("Attaching an ephemeral debug container to pod {}...", first),
+        "run" => format!("Starting a temporary debug pod {} {}...", first, scope),
+        "wait" => format!("Waiting for {} to satisfy {}...", first, args.iter().find_map(|a| a.strip_prefix("--for=")).unwrap_or("")),
+        "cp" => format!("Copying {} to {}...", first, second),
+        "version" => "Checking which kubectl version is installed...".to_string(),
+        "config" => "Reading your kubeconfig...".to_string(),
+        verb => format!("Running kubectl {}...", verb),
+    }
 }
 
-fn show_events(pod_pattern: &str, namespace: Option<String>) -> Result<()> {
-    let (pod_name, ns) = find_pod(pod_pattern, namespace)?;
+fn list_pods(list: &ListArgs) -> Result<()> {
+    let output_format = ListOutput::from_args(list)?;
+    let verbose = list.verbose;
     
-    println!("{} Events for pod: {} (namespace: {})", 
-        "[INFO]".cyan(), pod_name.bold(), ns.bright_black());
-    println!("{}", "-".repeat(100));
+    if list.watch.is_some() && !matches!(output_format, ListOutput::Table | ListOutput::Wide) {
+        anyhow::bail!("--watch only works with the table output (or -o wide)");
+    }
     
-    let status = Command::new("kubectl")
-        .args(&[
-            "get", "events",
-            "-n", &ns,
-            "--field-selector", &format!("involvedObject.name={}", pod_name),
-            "--sort-by", ".lastTimestamp",
-        ])
-        .status()?;
+    // kubectl renders these formats (e.g. the YAML), kdbg never parses them,
+    // so options that only shape kdbg's own table can't apply
+    if let ListOutput::Passthrough(spec) = &output_format {
+        let table_only = [
+            ("--owner", list.owner.is_some()),
+            ("--changed-since", list.changed_since.is_some()),
+            ("--count", list.count),
+            ("--group-by", list.group_by.is_some()),
+            ("--verbose", list.verbose),
+            ("--show-owner", list.show_owner),
+            ("--show-qos", list.show_qos),
+            ("--show-images", list.show_images || list.show_all_images),
+        ];
+        if let Some((flag, _)) = table_only.iter().find(|(_, set)| *set) {
+            anyhow::bail!("{} can't be combined with -o {}: kubectl renders that format", flag, spec);
+        }
+        return list_pods_passthrough(list, spec);
+    }
     
-    if !status.success() {
-        anyhow::bail!("Failed to get events");
+    let wide = matches!(output_format, ListOutput::Wide);
+    
+    if list.problems {
+        return list_problems(list);
     }
     
-    Ok(())
-}
-
-fn calculate_age(timestamp: &str) -> String {
-    use std::time::{SystemTime, UNIX_EPOCH};
+    if let Some(interval) = list.watch {
+        if list.json_lines {
+            return watch_pod_json_lines(list, interval);
+        }
+        return watch_pod_list(list, wide, interval);
+    }
     
-    let created = chrono::DateTime::parse_from_rfc3339(timestamp)
-        .map(|dt| dt.timestamp())
-        .unwrap_or(0);
+    let items = select_pods(list, wide)?;
+    let pods: Vec<&Value> = items.iter().collect();
     
-    let now = SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .unwrap()
-        .as_secs() as i64;
+    if list.count {
+        println!("{}", pods.len());
+        
+        if verbose {
+            let mut phases: std::collections::BTreeMap<&str, usize> = Default::default();
+            for pod in &pods {
+                *phases.entry(pod["status"]["phase"].as_str().unwrap_or("Unknown")).or_default() += 1;
+            }
+            for (phase, n) in phases {
+                println!("{} {}", phase, n);
+            }
+        }
+        
+        return Ok(());
+    }
     
-    let diff = now - created;
+    if let ListOutput::Json = output_format {
+        let list = serde_json::json!({ "apiVersion": "v1", "kind": "List", "items": pods });
+        println!("{}", serde_json::to_string_pretty(&list)?);
+        return Ok(());
+    }
     
-    if diff < 60 {
-        format!("{}s", diff)
-    } else if diff < 3600 {
-        format!("{}m", diff / 60)
-    } else if diff < 86400 {
-        format!("{}h", diff / 3600)
-    } else {
+    if let ListOutput::CustomColumns(columns) = &output_format {
+        if list.group_by.is_some() {
+            anyhow::bail!("--group-by can't be combined with -o custom-columns");
+        }
+        print!("{}", render_custom_columns(columns, &pods));
+        return Ok(());
+    }
+    
+    // The heading and footer go to stderr, so a pipe only gets the table
+    let options = RenderOptions::from_args(list, wide);
+    if options.title {
+        eprint!("{}", pod_list_title());
+    }
+    print!("{}", render_pod_list(&pods, &options));
+    if options.total {
+        eprint!("{}", pod_list_total(pods.len()));
+    }
+    
+    Ok(())
+}
+
+// How kdbg draws its own pod table, gathered once from list's flags rather
+// than each renderer picking through ListArgs
+struct RenderOptions {
+    title: bool,
+    total: bool,
+    verbose: bool,
+    wide: bool,
+    group_by: Option<GroupBy>,
+    columns: TableColumns,
+    restart_thresholds: RestartThresholds,
+}
+
+impl RenderOptions {
+    fn from_args(list: &ListArgs, wide: bool) -> RenderOptions {
+        RenderOptions {
+            title: !list.no_title,
+            total: !list.no_total,
+            verbose: list.verbose,
+            wide,
+            group_by: list.group_by,
+            columns: TableColumns {
+                owner: list.show_owner || wide,
+                qos: list.show_qos || list.verbose,
+                images: match (list.show_images, list.show_all_images) {
+                    (_, true) => ImageColumn::All,
+                    (true, false) => ImageColumn::Containers,
+                    (false, false) => ImageColumn::Hidden,
+                },
+            },
+            restart_thresholds: RestartThresholds::load(list.restart_threshold),
+        }
+    }
+}
+
+fn pod_list_title() -> String {
+    format!("{}\n{}\n", "Pods:".accent().bold(), "-".repeat(100))
+}
+
+fn pod_list_total(pods: usize) -> String {
+    format!("\nTotal: {} pods\n", pods)
+}
+
+// Renders the whole table into one buffer so --watch can draw a frame in a single write
+fn render_pod_list(pods: &[&Value], options: &RenderOptions) -> String {
+    use std::fmt::Write;
+    
+    let mut out = String::new();
+    
+    match options.group_by {
+        None => render_pod_table(&mut out, pods, options),
+        Some(group_by) => {
+            let mut groups: std::collections::BTreeMap<String, Vec<&Value>> = Default::default();
+            for pod in pods.iter().copied() {
+                groups.entry(group_key(pod, group_by)).or_default().push(pod);
+            }
+            
+            for (key, group) in &groups {
+                writeln!(out, "\n{} ({} pods)", key.bold(), group.len()).unwrap();
+                render_pod_table(&mut out, group, options);
+            }
+        }
+    }
+    
+    out
+}
+
+// Fetches pods and applies list's client-side filters
+fn select_pods(list: &ListArgs, wide: bool) -> Result<Vec<Value>> {
+    let namespace = namespace_arg(list.namespace.clone());
+    let status = list.status.as_deref();
+    let items = fetch_pods(&namespace, list.selector.as_deref())?;
+    let changed_after = list.changed_since.map(|window| chrono::Utc::now().timestamp() - window.as_secs() as i64);
+    
+    if list.show_owner || wide || list.owner.is_some() || matches!(list.group_by, Some(GroupBy::Owner)) {
+        // A namespace set is looked up cluster-wide rather than per namespace
+        load_replicaset_owners(namespace.as_deref().filter(|ns| !is_namespace_set(ns)));
+    }
+    
+    Ok(items
+        .into_iter()
+        .filter(|pod| {
+            let phase = pod["status"]["phase"].as_str().unwrap_or("Unknown");
+            status.is_none_or(|s| phase.eq_ignore_ascii_case(s))
+        })
+        .filter(|pod| list.owner.as_deref().is_none_or(|owner| owner_matches(pod, owner)))
+        .filter(|pod| changed_after.is_none_or(|after| pod_last_change(pod).is_some_and(|t| t >= after)))
+        .collect())
+}
+
+// How long a pod may stay Pending (or in its init containers), or linger past
+// its deletion deadline, before --problems calls it stuck
+const STUCK_AFTER_SECS: i64 = 300;
+const TERMINATING_GRACE_SECS: i64 = 60;
+
+// Warning events older than this no longer count against a pod
+const RECENT_WARNING_SECS: i64 = 3600;
+
+// The incident view: every pod that looks wrong, with all the reasons at once
+fn list_problems(list: &ListArgs) -> Result<()> {
+    let items = select_pods(list, false)?;
+    let options = RenderOptions::from_args(list, false);
+    let threshold = options.restart_thresholds.crit;
+    let warnings = recent_pod_warnings(&namespace_arg(list.namespace.clone()));
+    
+    let problems: Vec<(&Value, Vec<String>)> = items.iter()
+        .map(|pod| (pod, pod_problems(pod, threshold, &warnings)))
+        .filter(|(_, reasons)| !reasons.is_empty())
+        .collect();
+    
+    // Like the pod list, only the table goes to stdout
+    if problems.is_empty() {
+        eprintln!("{} No problems found in {} pods", "[SUCCESS]".success(), items.len());
+        return Ok(());
+    }
+    
+    if options.title {
+        eprintln!("{}\n{}", "Pods with problems:".danger().bold(), "-".repeat(100));
+    }
+    println!("{:<40} {:<15} {:<12} PROBLEMS", "NAME", "NAMESPACE", "STATUS");
+    println!("{}", "-".repeat(100));
+    for (pod, reasons) in &problems {
+        let (name, ns) = pod_name_ns(pod);
+        let phase = pod_phase(pod);
+        println!("{} {} {} {}", 
+            pad(&name, 40).accent(), namespace_colored(&ns, &pad(&ns, 15)), phase_colored(phase, &pad(phase, 12)), reasons.join("; "));
+    }
+    if options.total {
+        eprintln!("\nTotal: {} of {} pods", problems.len(), items.len());
+    }
+    
+    anyhow::bail!("{} pods have problems", problems.len())
+}
+
+fn pod_problems(pod: &Value, restart_threshold: u64, warnings: &BTreeMap<(String, String), Value>) -> Vec<String> {
+    let now = chrono::Utc::now().timestamp();
+    let timestamp = |field: &Value| field.as_str()
+        .and_then(|ts| chrono::DateTime::parse_from_rfc3339(ts).ok())
+        .map(|dt| dt.timestamp());
+    let created = pod["metadata"]["creationTimestamp"].as_str().unwrap_or("");
+    let reason = pod_reason(pod).map(|r| format!(" ({})", r)).unwrap_or_default();
+    let mut problems = Vec::new();
+    
+    match pod_phase(pod) {
+        "Terminating" if timestamp(&pod["metadata"]["deletionTimestamp"]).is_some_and(|t| now - t > TERMINATING_GRACE_SECS) => {
+            let deadline = pod["metadata"]["deletionTimestamp"].as_str().unwrap_or("");
+            problems.push(format!("still terminating {} past its grace period", calculate_age(deadline)));
+        }
+        phase @ ("Failed" | "Unknown") => problems.push(format!("{}{}", phase, reason)),
+        "Pending" if timestamp(&pod["metadata"]["creationTimestamp"]).is_some_and(|t| now - t > STUCK_AFTER_SECS) => {
+            let init = pod["status"]["initContainerStatuses"].as_array().map(Vec::as_slice).unwrap_or_default();
+            let done = init.iter().filter(|c| c["state"]["terminated"]["exitCode"] == 0).count();
+            let unschedulable = pod["status"]["conditions"].as_array().into_iter().flatten()
+                .find(|c| c["type"] == "PodScheduled" && c["status"] == "False")
+                .and_then(|c| c["reason"].as_str())
+                .map(|r| format!(" ({})", r));
+            
+            if done < init.len() {
+                let init_reason = init.iter().find_map(|c| c["state"]["waiting"]["reason"].as_str())
+                    .map(|r| format!(" ({})", r))
+                    .unwrap_or_default();
+                problems.push(format!("stuck in Init:{}/{} for {}{}", done, init.len(), calculate_age(created), init_reason));
+            } else {
+                problems.push(format!("Pending for {}{}", calculate_age(created), unschedulable.unwrap_or(reason)));
+            }
+        }
+        "Running" if !is_pod_ready(pod) => problems.push(format!("not ready{}", reason)),
+        _ => {}
+    }
+    
+    let restarts = restart_count(pod);
+    if restarts >= restart_threshold {
+        problems.push(format!("{} restarts", restarts));
+    }
+    
+    let (name, ns) = pod_name_ns(pod);
+    if let Some(event) = warnings.get(&(ns, name)) {
+        problems.push(format!("warning {} x{}, {} ago", 
+            event["reason"].as_str().unwrap_or("event"), event_count(event), calculate_age(&event_rfc3339(event))));
+    }
+    
+    problems
+}
+
+// Latest Warning event per pod from the last RECENT_WARNING_SECS. Best effort:
+// without events the other checks still run
+fn recent_pod_warnings(namespace: &Option<String>) -> BTreeMap<(String, String), Value> {
+    let scope = match namespace {
+        Some(ns) if !is_namespace_set(ns) => vec!["-n", ns],
+        _ => vec!["--all-namespaces"],
+    };
+    let scope: Vec<&str> = scope.into_iter().chain(["--field-selector", "type=Warning,involvedObject.kind=Pod"]).collect();
+    let events = match fetch_events(&scope) {
+        Ok(events) => events,
+        Err(e) => {
+            eprintln!("{} Skipping the Warning event check: {}", "[WARN]".warning(), e);
+            return BTreeMap::new();
+        }
+    };
+    
+    let since = chrono::Utc::now().timestamp() - RECENT_WARNING_SECS;
+    let mut latest: BTreeMap<(String, String), Value> = BTreeMap::new();
+    for event in events.into_iter().filter(|e| e["type"] == "Warning" && event_timestamp(e, "lastTimestamp") >= since) {
+        let key = (
+            event["metadata"]["namespace"].as_str().unwrap_or("default").to_string(),
+            event["involvedObject"]["name"].as_str().unwrap_or("").to_string(),
+        );
+        if latest.get(&key).is_none_or(|seen| event_timestamp(seen, "lastTimestamp") <= event_timestamp(&event, "lastTimestamp")) {
+            latest.insert(key, event);
+        }
+    }
+    latest
+}
+
+fn event_rfc3339(event: &Value) -> String {
+    chrono::DateTime::from_timestamp(event_timestamp(event, "lastTimestamp"), 0)
+        .map(|dt| dt.to_rfc3339())
+        .unwrap_or_default()
+}
+
+// Latest of the pod's creation, a container start, or a container's last
+// termination, so restarts count as changes as well as new pods
+fn pod_last_change(pod: &Value) -> Option<i64> {
+    let statuses = pod["status"]["containerStatuses"].as_array().into_iter().flatten();
+    
+    std::iter::once(&pod["metadata"]["creationTimestamp"])
+        .chain(statuses.flat_map(|c| [&c["state"]["running"]["startedAt"], &c["lastState"]["terminated"]["finishedAt"]]))
+        .filter_map(|v| v.as_str())
+        .filter_map(|ts| chrono::DateTime::parse_from_rfc3339(ts).ok())
+        .map(|dt| dt.timestamp())
+        .max()
+}
+
+// Redraws the table every interval on the alternate screen until q or Ctrl+C.
+// Each frame goes out in one write (cursor home, lines cleared to their end,
+// then the rest of the screen) so refreshes don't flicker.
+fn watch_pod_list(list: &ListArgs, wide: bool, interval: u64) -> Result<()> {
+    let options = RenderOptions::from_args(list, wide);
+    
+    watch_screen("kdbg list", interval, || {
+        let body = match select_pods(list, wide) {
+            Ok(items) => {
+                let title = if options.title { pod_list_title() } else { String::new() };
+                let total = if options.total { pod_list_total(items.len()) } else { String::new() };
+                format!("{}{}{}", title, render_pod_list(&items.iter().collect::<Vec<_>>(), &options), total)
+            }
+            Err(e) => format!("{} {}\n", "[ERROR]".danger(), e),
+        };
+        (body, false)
+    })?;
+    
+    Ok(())
+}
+
+// A change feed for scripts: every refresh prints the whole pod set as JSON
+// lines, and pods are matched to the previous refresh by UID to tell which
+// ones were added, changed or removed in between
+fn watch_pod_json_lines(list: &ListArgs, interval: u64) -> Result<()> {
+    use std::io::Write;
+    
+    let mut previous: Option<BTreeMap<String, Value>> = None;
+    
+    loop {
+        match select_pods(list, false) {
+            Ok(items) => {
+                let (out, current) = json_lines_frame(previous.as_ref(), items);
+                
+                // The reader going away (e.g. `| head`) ends the watch
+                let mut stdout = std::io::stdout().lock();
+                if stdout.write_all(out.as_bytes()).and_then(|_| stdout.flush()).is_err() {
+                    return Ok(());
+                }
+                
+                previous = Some(current);
+            }
+            Err(e) => eprintln!("{} {}", "[WARN]".warning(), e),
+        }
+        
+        std::thread::sleep(std::time::Duration::from_secs(interval));
+    }
+}
+
+// One refresh's JSON lines plus the pods by UID to compare the next refresh
+// against. Without a previous refresh (the first one) no pod has an _event.
+fn json_lines_frame(previous: Option<&BTreeMap<String, Value>>, items: Vec<Value>) -> (String, BTreeMap<String, Value>) {
+    let mut out = String::new();
+    let mut current = BTreeMap::new();
+    
+    for pod in items {
+        let uid = pod_uid(&pod);
+        let event = previous.and_then(|previous| match previous.get(&uid) {
+            None => Some("added"),
+            Some(old) if *old != pod => Some("changed"),
+            Some(_) => None,
+        });
+        out.push_str(&pod_json_line(&pod, event));
+        current.insert(uid, pod);
+    }
+    
+    for (uid, pod) in previous.into_iter().flatten() {
+        if !current.contains_key(uid) {
+            out.push_str(&pod_json_line(pod, Some("removed")));
+        }
+    }
+    
+    (out, current)
+}
+
+fn pod_uid(pod: &Value) -> String {
+    match pod["metadata"]["uid"].as_str() {
+        Some(uid) => uid.to_string(),
+        None => {
+            let (name, ns) = pod_name_ns(pod);
+            format!("{}/{}", ns, name)
+        }
+    }
+}
+
+fn pod_json_line(pod: &Value, event: Option<&str>) -> String {
+    let mut pod = pod.clone();
+    if let (Some(event), Some(object)) = (event, pod.as_object_mut()) {
+        object.insert("_event".to_string(), Value::from(event));
+    }
+    format!("{}\n", pod)
+}
+
+// Redraws `render`'s frame every `interval` seconds on the alternate screen
+// until it reports done (returning the last frame, for the caller to print on
+// the normal screen) or the user quits with q / Ctrl+C (returning None)
+fn watch_screen(title: &str, interval: u64, mut render: impl FnMut() -> (String, bool)) -> Result<Option<String>> {
+    use std::io::{IsTerminal, Write};
+    use std::sync::mpsc;
+    
+    if !std::io::stdout().is_terminal() || !std::io::stdin().is_terminal() {
+        anyhow::bail!("--watch needs an interactive terminal");
+    }
+    
+    // No line buffering or echo, and Ctrl+C arrives as a key so the screen can be restored
+    let saved = stty(&["-g"])?;
+    stty(&["-icanon", "-echo", "-isig"])?;
+    print!("\x1b[?1049h\x1b[?25l");
+    
+    let (keys, key_rx) = mpsc::channel();
+    std::thread::spawn(move || {
+        while let Ok(key) = read_byte() {
+            if keys.send(key).is_err() {
+                break;
+            }
+        }
+    });
+    
+    let result = (|| -> Result<Option<String>> {
+        loop {
+            let (body, done) = render();
+            let frame = format!("{}\n\n{}", 
+                format!("Every {}s: {}    last refresh {}    (q to quit)", interval, title, chrono::Local::now().format("%H:%M:%S")).muted(),
+                body);
+            
+            let mut stdout = std::io::stdout().lock();
+            write!(stdout, "\x1b[H{}\x1b[J", frame.replace('\n', "\x1b[K\n"))?;
+            stdout.flush()?;
+            drop(stdout);
+            
+            if done {
+                return Ok(Some(body));
+            }
+            
+            match key_rx.recv_timeout(std::time::Duration::from_secs(interval)) {
+                Ok(b'q') | Ok(3) | Err(mpsc::RecvTimeoutError::Disconnected) => return Ok(None),
+                _ => {}
+            }
+        }
+    })();
+    
+    print!("\x1b[?25h\x1b[?1049l");
+    std::io::stdout().flush()?;
+    stty(&[saved.trim()])?;
+    
+    result
+}
+
+// Resources that live outside namespaces; kubectl rejects or ignores -n for them
+const CLUSTER_SCOPED: &[&str] = &[
+    "nodes", "node", "no",
+    "namespaces", "namespace", "ns",
+    "persistentvolumes", "persistentvolume", "pv",
+    "storageclasses", "storageclass", "sc",
+    "clusterroles", "clusterrole",
+    "clusterrolebindings", "clusterrolebinding",
+    "customresourcedefinitions", "customresourcedefinition", "crd", "crds",
+    "priorityclasses", "priorityclass", "pc",
+    "ingressclasses", "ingressclass",
+    "runtimeclasses", "runtimeclass",
+    "csidrivers", "csidriver", "csinodes", "csinode",
+    "volumeattachments", "volumeattachment",
+    "mutatingwebhookconfigurations", "validatingwebhookconfigurations",
+    "apiservices", "apiservice",
+];
+
+fn is_pod_resource(resource: &str) -> bool {
+    matches!(resource, "pods" | "pod" | "po")
+}
+
+fn is_cluster_scoped(resource: &str) -> bool {
+    // Accept group-qualified names too, e.g. storageclasses.storage.k8s.io
+    let kind = resource.split('.').next().unwrap_or(resource).to_lowercase();
+    CLUSTER_SCOPED.contains(&kind.as_str())
+}
+
+// Non-pod resources are rendered by kubectl; only scoping, -l and -o carry over
+fn list_resources(list: &ListArgs, resource: &str) -> Result<()> {
+    if list.verbose || list.group_by.is_some() || list.status.is_some() || list.count
+        || list.owner.is_some() || list.changed_since.is_some() || list.watch.is_some() || list.show_owner || list.show_qos
+        || list.show_images || list.show_all_images || list.problems
+    {
+        anyhow::bail!("Only -n, -l and -o apply to `kdbg list {}`; the other options are pod-specific", resource);
+    }
+    
+    let namespace = namespace_arg(list.namespace.clone());
+    let mut args = vec!["get", resource];
+    
+    if is_cluster_scoped(resource) {
+        if let Some(ns) = &namespace {
+            eprintln!("{} {} are cluster-scoped, ignoring namespace {}", "[INFO]".accent(), resource, ns);
+        }
+    } else {
+        single_namespace(&namespace)?;
+        match &namespace {
+            Some(ns) => args.extend(&["-n", ns]),
+            None => args.push("--all-namespaces"),
+        }
+    }
+    
+    if let Some(sel) = &list.selector {
+        args.extend(&["-l", sel]);
+    }
+    if let Some(output) = &list.output {
+        args.extend(&["-o", output]);
+    }
+    
+    let status = kubectl(&args).status()?;
+    
+    if !status.success() {
+        anyhow::bail!("kubectl get {} failed", resource);
+    }
+    
+    Ok(())
+}
+
+fn list_pods_raw(list: &ListArgs) -> Result<()> {
+    list_pods_passthrough(list, "")
+}
+
+// Lets kubectl render the pods itself, with kdbg's scoping and filters applied
+fn list_pods_passthrough(list: &ListArgs, output: &str) -> Result<()> {
+    let namespace = namespace_arg(list.namespace.clone());
+    single_namespace(&namespace)?;
+    
+    let args = passthrough_args(list, namespace.as_deref(), output);
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+    let status = kubectl(&args).status()?;
+    
+    if !status.success() {
+        anyhow::bail!("kubectl get pods failed");
+    }
+    
+    Ok(())
+}
+
+fn passthrough_args(list: &ListArgs, namespace: Option<&str>, output: &str) -> Vec<String> {
+    let mut args = vec!["get".to_string(), "pods".to_string()];
+    
+    if let Some(ns) = namespace {
+        args.extend(["-n".to_string(), ns.to_string()]);
+    } else {
+        args.push("--all-namespaces".to_string());
+    }
+    
+    if let Some(sel) = &list.selector {
+        args.extend(["-l".to_string(), sel.clone()]);
+    }
+    
+    if let Some(status) = &list.status {
+        args.extend(["--field-selector".to_string(), format!("status.phase={}", status)]);
+    }
+    
+    if !output.is_empty() {
+        args.extend(["-o".to_string(), output.to_string()]);
+    }
+    
+    args
+}
+
+// Optional columns after the fixed NAME/NAMESPACE/STATUS ones
+struct TableColumns {
+    owner: bool,
+    qos: bool,
+    images: ImageColumn,
+}
+
+#[derive(PartialEq)]
+enum ImageColumn {
+    Hidden,
+    Containers,
+    // Containers plus init containers
+    All,
+}
+
+fn render_pod_table(out: &mut String, pods: &[&Value], options: &RenderOptions) {
+    use std::fmt::Write;
+    
+    let (verbose, wide, columns) = (options.verbose, options.wide, &options.columns);
+    let thresholds = options.restart_thresholds;
+    let mut wide_header = if wide { format!(" {:<20} {:<15}", "NODE", "IP") } else { String::new() };
+    if columns.owner {
+        wide_header.push_str(&format!(" {:<30}", "CONTROLLER"));
+    }
+    if columns.qos {
+        wide_header.push_str(&format!(" {:<22}", "QOS"));
+    }
+    if columns.images != ImageColumn::Hidden {
+        wide_header.push_str(" IMAGES");
+    }
+    
+    if verbose {
+        writeln!(out, "{:<40} {:<15} {:<10} {:<15} {:<20}{}", 
+            "NAME", "NAMESPACE", "STATUS", "RESTARTS", "AGE", wide_header).unwrap();
+        writeln!(out, "{}", "-".repeat(100)).unwrap();
+    } else {
+        writeln!(out, "{:<40} {:<15} {:<10}{}", "NAME", "NAMESPACE", "STATUS", wide_header).unwrap();
+        writeln!(out, "{}", "-".repeat(100)).unwrap();
+    }
+    
+    for pod in pods {
+        let name = pod["metadata"]["name"].as_str().unwrap_or("unknown");
+        let ns = pod["metadata"]["namespace"].as_str().unwrap_or("default");
+        let phase = pod["status"]["phase"].as_str().unwrap_or("Unknown");
+        
+        let status_colored = phase_colored(phase, phase);
+        
+        let mut wide_columns = if wide {
+            let node = pod["spec"]["nodeName"].as_str().unwrap_or("<none>");
+            let ip = pod["status"]["podIP"].as_str().unwrap_or("<none>");
+            format!(" {} {}", pad(node, 20), pad(ip, 15))
+        } else {
+            String::new()
+        };
+        
+        if columns.owner {
+            let controller = pod_owner(pod)
+                .map(|(kind, name)| format!("{}/{}", kind, name))
+                .unwrap_or_else(|| "<none>".to_string());
+            wide_columns.push_str(&format!(" {}", pad(&controller, 30)));
+        }
+        
+        if columns.qos {
+            wide_columns.push_str(&format!(" {}", qos_column(pod)));
+        }
+        
+        // Last column, so it's left unpadded however many images there are
+        if columns.images != ImageColumn::Hidden {
+            wide_columns.push_str(&format!(" {}", pod_images(pod, columns.images == ImageColumn::All)));
+        }
+        
+        let restarts = restart_count(pod);
+        
+        if verbose {
+            let restarts_colored = thresholds.colored(restarts, &format!("{:<15}", restarts));
+            
+            let age = pod["metadata"]["creationTimestamp"]
+                .as_str()
+                .map(calculate_age)
+                .unwrap_or("unknown".to_string());
+            
+            writeln!(out, "{} {} {:<10} {} {:<20}{}", 
+                pad(name, 40).accent(), namespace_colored(ns, &pad(ns, 15)), status_colored, restarts_colored, age, wide_columns).unwrap();
+        } else {
+            // Trails the row so the columns after STATUS stay aligned
+            let marker = if restarts >= thresholds.crit { format!(" (⟳{})", restarts).danger().bold() } else { "".normal() };
+            writeln!(out, "{} {} {:<10}{}{}", 
+                pad(name, 40).accent(), namespace_colored(ns, &pad(ns, 15)), status_colored, wide_columns, marker).unwrap();
+        }
+    }
+}
+
+// The colors behind each role; rendering code asks for a role (`.accent()`,
+// `.warning()`, ...) through Themed rather than naming a color
+struct Theme {
+    // Pod names, headings and [INFO]
+    accent: Color,
+    // Secondary details: namespaces in headers, hints, timestamps
+    muted: Color,
+    success: Color,
+    warning: Color,
+    danger: Color,
+    // Namespaces a row can be colored with; the role colors are left out
+    // since they already mean pod name and status
+    namespaces: &'static [Color],
+}
+
+static THEME: OnceLock<Theme> = OnceLock::new();
+
+const DEFAULT_THEME: Theme = Theme {
+    accent: Color::Cyan,
+    muted: Color::BrightBlack,
+    success: Color::Green,
+    warning: Color::Yellow,
+    danger: Color::Red,
+    namespaces: &[Color::Blue, Color::Magenta, Color::BrightBlue, Color::BrightMagenta, Color::BrightCyan, Color::BrightWhite],
+};
+
+// For dark themes where bright black disappears into the background
+const HIGH_CONTRAST_THEME: Theme = Theme {
+    accent: Color::BrightCyan,
+    muted: Color::White,
+    success: Color::BrightGreen,
+    warning: Color::BrightYellow,
+    danger: Color::BrightRed,
+    namespaces: &[Color::BrightBlue, Color::BrightMagenta, Color::BrightWhite, Color::Blue, Color::Magenta],
+};
+
+// Yellow and the bright colors wash out on a white background
+const LIGHT_THEME: Theme = Theme {
+    accent: Color::Blue,
+    muted: Color::BrightBlack,
+    success: Color::Green,
+    warning: Color::Magenta,
+    danger: Color::Red,
+    namespaces: &[Color::Cyan, Color::BrightBlue, Color::Black, Color::BrightMagenta],
+};
+
+impl Theme {
+    fn load(name: Option<ThemeName>) -> Theme {
+        let name = name.or_else(|| {
+            let configured = env_or_config("KDBG_THEME", "theme")?;
+            let parsed = ThemeName::from_str(&configured, true).ok();
+            if parsed.is_none() {
+                eprintln!("{} Unknown theme '{}'; expected default, high-contrast or light", "[WARN]".warning(), configured);
+            }
+            parsed
+        });
+        
+        match name.unwrap_or(ThemeName::Default) {
+            ThemeName::Default => DEFAULT_THEME,
+            ThemeName::HighContrast => HIGH_CONTRAST_THEME,
+            ThemeName::Light => LIGHT_THEME,
+        }
+    }
+}
+
+fn theme() -> &'static Theme {
+    THEME.get().unwrap_or(&DEFAULT_THEME)
+}
+
+trait Themed: Colorize + Sized {
+    fn accent(self) -> ColoredString {
+        self.color(theme().accent)
+    }
+    
+    fn muted(self) -> ColoredString {
+        self.color(theme().muted)
+    }
+    
+    fn success(self) -> ColoredString {
+        self.color(theme().success)
+    }
+    
+    fn warning(self) -> ColoredString {
+        self.color(theme().warning)
+    }
+    
+    fn danger(self) -> ColoredString {
+        self.color(theme().danger)
+    }
+}
+
+impl<T: Colorize> Themed for T {}
+
+// FNV-1a rather than std's hasher, whose output isn't guaranteed to stay
+// the same between Rust releases; a namespace should keep its color
+fn namespace_colored(namespace: &str, text: &str) -> ColoredString {
+    let hash = namespace.bytes().fold(0xcbf29ce484222325u64, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+    });
+    let colors = theme().namespaces;
+    text.color(colors[(hash % colors.len() as u64) as usize])
+}
+
+fn phase_colored(phase: &str, text: &str) -> ColoredString {
+    match phase {
+        "Running" => text.success(),
+        "Pending" | "Terminating" => text.warning(),
+        "Failed" => text.danger(),
+        "Succeeded" => text.blue(),
+        _ => text.normal(),
+    }
+}
+
+// Laid out like kubectl's custom-columns (three spaces between columns,
+// <none> for missing fields), with the name, namespace and phase colored
+// as in the default table
+struct CustomColumn {
+    header: String,
+    source: ColumnSource,
+}
+
+enum ColumnSource {
+    Path(String),
+    // One of BUILTIN_COLUMNS, computed the way kdbg's own table does
+    Builtin(&'static str),
+}
+
+const BUILTIN_COLUMNS: &[&str] = &[
+    "name", "namespace", "status", "restarts", "age", "reason", "node", "ip", "qos", "owner", "images",
+];
+
+// Columns are HEADER:.json.path as in kubectl, or a shorthand: a builtin
+// name (restarts, age, ...) or a bare path like status.phase, whose header
+// is derived from the last segment. Commas or newlines separate columns and
+// # starts a comment, so saved specs can be one column per line.
+fn parse_columns(spec: &str) -> Result<Vec<CustomColumn>> {
+    let columns: Vec<CustomColumn> = spec
+        .lines()
+        .map(strip_toml_comment)
+        .flat_map(|line| line.split(','))
+        .map(str::trim)
+        .filter(|column| !column.is_empty())
+        .map(|column| match column.split_once(':') {
+            Some((header, path)) if !header.is_empty() && !path.is_empty() => Ok(CustomColumn {
+                header: header.to_string(),
+                source: ColumnSource::Path(path.to_string()),
+            }),
+            Some(_) => anyhow::bail!("Invalid custom column '{}', expected HEADER:.json.path", column),
+            None => match BUILTIN_COLUMNS.iter().find(|b| b.eq_ignore_ascii_case(column)) {
+                Some(builtin) => Ok(CustomColumn { header: builtin.to_uppercase(), source: ColumnSource::Builtin(builtin) }),
+                None => {
+                    let path = if column.starts_with(['.', '{']) { column.to_string() } else { format!(".{}", column) };
+                    let header = path.trim_matches(['{', '}']).rsplit('.').next().unwrap_or(column).to_uppercase();
+                    Ok(CustomColumn { header, source: ColumnSource::Path(path) })
+                }
+            },
+        })
+        .collect::<Result<_>>()?;
+    
+    if columns.is_empty() {
+        anyhow::bail!("The column spec is empty");
+    }
+    
+    Ok(columns)
+}
+
+fn column_value(pod: &Value, source: &ColumnSource) -> String {
+    let field = |v: &Value| v.as_str().unwrap_or("<none>").to_string();
+    
+    match source {
+        ColumnSource::Path(path) => {
+            let values: Vec<String> = json_path(pod, path)
+                .into_iter()
+                .filter(|v| !v.is_null())
+                .map(|v| v.as_str().map(str::to_string).unwrap_or_else(|| v.to_string()))
+                .collect();
+            if values.is_empty() { "<none>".to_string() } else { values.join(",") }
+        }
+        ColumnSource::Builtin("name") => field(&pod["metadata"]["name"]),
+        ColumnSource::Builtin("namespace") => field(&pod["metadata"]["namespace"]),
+        ColumnSource::Builtin("status") => pod_phase(pod).to_string(),
+        ColumnSource::Builtin("restarts") => restart_count(pod).to_string(),
+        ColumnSource::Builtin("age") => pod["metadata"]["creationTimestamp"].as_str().map(calculate_age).unwrap_or_default(),
+        ColumnSource::Builtin("reason") => pod_reason(pod).unwrap_or("<none>").to_string(),
+        ColumnSource::Builtin("node") => field(&pod["spec"]["nodeName"]),
+        ColumnSource::Builtin("ip") => field(&pod["status"]["podIP"]),
+        ColumnSource::Builtin("qos") => field(&pod["status"]["qosClass"]),
+        ColumnSource::Builtin("owner") => pod_owner(pod)
+            .map(|(kind, name)| format!("{}/{}", kind, name))
+            .unwrap_or_else(|| "<none>".to_string()),
+        ColumnSource::Builtin(_) => pod_images(pod, false),
+    }
+}
+
+// Phase as kubectl shows it: pods being deleted read Terminating
+fn pod_phase(pod: &Value) -> &str {
+    if pod["metadata"]["deletionTimestamp"].is_null() {
+        pod["status"]["phase"].as_str().unwrap_or("Unknown")
+    } else {
+        "Terminating"
+    }
+}
+
+// Why a pod isn't healthy: Evicted and friends, else the first container
+// that is waiting (CrashLoopBackOff, ImagePullBackOff) or terminated (OOMKilled)
+fn pod_reason(pod: &Value) -> Option<&str> {
+    pod["status"]["reason"].as_str().or_else(|| {
+        pod["status"]["containerStatuses"].as_array()?.iter().find_map(|c| {
+            c["state"]["waiting"]["reason"].as_str().or_else(|| c["state"]["terminated"]["reason"].as_str())
+        })
+    })
+}
+
+fn render_custom_columns(columns: &[CustomColumn], pods: &[&Value]) -> String {
+    use std::fmt::Write;
+    
+    let rows: Vec<Vec<String>> = pods
+        .iter()
+        .map(|pod| columns.iter().map(|column| column_value(pod, &column.source)).collect())
+        .collect();
+    
+    let widths: Vec<usize> = columns
+        .iter()
+        .enumerate()
+        .map(|(i, column)| {
+            rows.iter().map(|row| display_width(&row[i])).chain([display_width(&column.header)]).max().unwrap_or(0)
+        })
+        .collect();
+    
+    let mut out = String::new();
+    let last = columns.len() - 1;
+    
+    for (i, column) in columns.iter().enumerate() {
+        let cell = if i == last { column.header.clone() } else { format!("{}   ", pad(&column.header, widths[i])) };
+        out.push_str(&cell);
+    }
+    out.push('\n');
+    
+    for row in &rows {
+        for (i, (column, value)) in columns.iter().zip(row).enumerate() {
+            let cell = if i == last { value.clone() } else { pad(value, widths[i]) };
+            let key = match &column.source {
+                ColumnSource::Path(path) => path.trim_matches(['{', '}']),
+                ColumnSource::Builtin(name) => name,
+            };
+            let colored = match key {
+                ".metadata.name" | "name" => cell.accent(),
+                ".metadata.namespace" | "namespace" => namespace_colored(value, &cell),
+                ".status.phase" | "status" => phase_colored(value, &cell),
+                "reason" if value != "<none>" => cell.danger(),
+                _ => cell.normal(),
+            };
+            write!(out, "{}{}", colored, if i == last { "" } else { "   " }).unwrap();
+        }
+        out.push('\n');
+    }
+    
+    out
+}
+
+// The JSONPath subset custom columns use: .field, .field[N], .field[*]
+// (also bare [*]), optional {} braces, and \. for dots inside keys such as
+// .metadata.labels.app\.kubernetes\.io/name
+fn json_path<'a>(value: &'a Value, path: &str) -> Vec<&'a Value> {
+    let path = path.trim();
+    let path = path.strip_prefix('{').and_then(|p| p.strip_suffix('}')).unwrap_or(path);
+    
+    let mut segments = Vec::new();
+    let mut current = String::new();
+    let mut chars = path.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' if chars.peek() == Some(&'.') => current.push(chars.next().unwrap()),
+            '.' => segments.push(std::mem::take(&mut current)),
+            _ => current.push(c),
+        }
+    }
+    segments.push(current);
+    
+    let mut matches = vec![value];
+    for segment in segments.iter().filter(|s| !s.is_empty()) {
+        let (key, indexes) = match segment.find('[') {
+            Some(i) => segment.split_at(i),
+            None => (segment.as_str(), ""),
+        };
+        
+        if !key.is_empty() {
+            matches = matches.into_iter().filter_map(|v| v.get(key)).collect();
+        }
+        
+        for index in indexes.split(']').filter(|i| !i.is_empty()) {
+            let index = index.trim_start_matches('[');
+            matches = match index {
+                "*" => matches.into_iter().flat_map(|v| v.as_array().into_iter().flatten()).collect(),
+                n => match n.parse::<usize>() {
+                    Ok(n) => matches.into_iter().filter_map(|v| v.get(n)).collect(),
+                    Err(_) => Vec::new(),
+                },
+            };
+        }
+    }
+    
+    matches
+}
+
+fn pod_images(pod: &Value, include_init: bool) -> String {
+    let image = |c: &Value| c["image"].as_str().unwrap_or("<none>").to_string();
+    
+    let init = pod["spec"]["initContainers"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter(|_| include_init)
+        .map(|c| format!("init:{}", image(c)));
+    let containers = pod["spec"]["containers"].as_array().into_iter().flatten().map(image);
+    
+    let images: Vec<String> = init.chain(containers).collect();
+    if images.is_empty() { "<none>".to_string() } else { images.join(",") }
+}
+
+// QoS class colored by how early the kubelet evicts it under node pressure
+// (BestEffort first, Guaranteed last), flagged when eviction is underway
+fn qos_column(pod: &Value) -> ColoredString {
+    let qos = pod["status"]["qosClass"].as_str().unwrap_or("<none>");
+    
+    let evicting = pod["status"]["reason"].as_str() == Some("Evicted")
+        || pod["status"]["conditions"].as_array().is_some_and(|conditions| {
+            conditions.iter().any(|c| c["type"] == "DisruptionTarget" && c["status"] == "True")
+        });
+    
+    if evicting {
+        return pad(&format!("{} (evicting)", qos), 22).danger().bold();
+    }
+    
+    let text = pad(qos, 22);
+    match qos {
+        "BestEffort" => text.danger(),
+        "Burstable" => text.warning(),
+        "Guaranteed" => text.success(),
+        _ => text.normal(),
+    }
+}
+
+// Left-aligns to a terminal column width; `{:<N}` counts chars, which
+// misaligns wide (CJK, emoji) and zero-width (combining) characters
+fn pad(text: &str, width: usize) -> String {
+    let used = display_width(text);
+    format!("{}{}", text, " ".repeat(width.saturating_sub(used)))
+}
+
+// unicode-width counts control characters (C0 and C1) as one column in a str;
+// a terminal doesn't advance for them
+fn display_width(text: &str) -> usize {
+    if text.chars().any(char::is_control) {
+        let visible: String = text.chars().filter(|c| !c.is_control()).collect();
+        return unicode_width::UnicodeWidthStr::width(visible.as_str());
+    }
+    unicode_width::UnicodeWidthStr::width(text)
+}
+
+// Control characters take no column
+fn char_width(c: char) -> usize {
+    unicode_width::UnicodeWidthChar::width(c).unwrap_or(0)
+}
+
+fn group_key(pod: &Value, group_by: GroupBy) -> String {
+    match group_by {
+        GroupBy::Node => pod["spec"]["nodeName"].as_str().unwrap_or("<unscheduled>").to_string(),
+        GroupBy::Namespace => pod["metadata"]["namespace"].as_str().unwrap_or("default").to_string(),
+        GroupBy::Owner => pod_owner(pod)
+            .map(|(kind, name)| format!("{}/{}", kind, name))
+            .unwrap_or_else(|| "<none>".to_string()),
+    }
+}
+
+// Resolves the pod's top-level controller. ReplicaSets are dereferenced to their
+// Deployment through the replicaset lookup when it has been loaded; otherwise the
+// Deployment is derived from the "<deployment>-<pod-template-hash>" naming.
+fn pod_owner(pod: &Value) -> Option<(String, String)> {
+    let owner = pod["metadata"]["ownerReferences"]
+        .as_array()?
+        .iter()
+        .find(|o| o["controller"].as_bool().unwrap_or(false))?;
+    
+    let kind = owner["kind"].as_str()?;
+    let name = owner["name"].as_str()?;
+    
+    if kind == "ReplicaSet"
+        && let Some(replicasets) = REPLICASET_OWNERS.get()
+    {
+        let ns = pod["metadata"]["namespace"].as_str().unwrap_or("default");
+        if let Some(owner) = replicasets.get(&(ns.to_string(), name.to_string())) {
+            return Some(owner.clone().unwrap_or_else(|| (kind.to_string(), name.to_string())));
+        }
+    }
+    
+    if kind == "ReplicaSet"
+        && let Some(hash) = pod["metadata"]["labels"]["pod-template-hash"].as_str()
+        && let Some(deployment) = name.strip_suffix(&format!("-{}", hash))
+    {
+        return Some(("Deployment".to_string(), deployment.to_string()));
+    }
+    
+    Some((kind.to_string(), name.to_string()))
+}
+
+// (namespace, replicaset) -> the replicaset's own controller, if any
+type ReplicaSetOwners = BTreeMap<(String, String), Option<(String, String)>>;
+
+static REPLICASET_OWNERS: OnceLock<ReplicaSetOwners> = OnceLock::new();
+
+// Fetches replicasets once per run so pod_owner can dereference them. Failures
+// (e.g. no list permission on replicasets) leave pod_owner on its name heuristic.
+fn load_replicaset_owners(namespace: Option<&str>) {
+    REPLICASET_OWNERS.get_or_init(|| {
+        let mut args = vec!["get", "replicasets", "-o", "json"];
+        match namespace {
+            Some(ns) => args.extend(&["-n", ns]),
+            None => args.push("--all-namespaces"),
+        }
+        
+        let json: Value = match run_kubectl(&args) {
+            Ok(output) if output.status.success() => {
+                serde_json::from_slice(&output.stdout).unwrap_or_default()
+            }
+            _ => return BTreeMap::new(),
+        };
+        
+        let mut owners = BTreeMap::new();
+        for rs in json["items"].as_array().into_iter().flatten() {
+            let (Some(ns), Some(name)) = (rs["metadata"]["namespace"].as_str(), rs["metadata"]["name"].as_str()) else {
+                continue;
+            };
+            
+            let owner = rs["metadata"]["ownerReferences"]
+                .as_array()
+                .and_then(|refs| refs.iter().find(|o| o["controller"].as_bool().unwrap_or(false)))
+                .and_then(|o| Some((o["kind"].as_str()?.to_string(), o["name"].as_str()?.to_string())));
+            
+            owners.insert((ns.to_string(), name.to_string()), owner);
+        }
+        owners
+    });
+}
+
+// Matches "name" against the controller name, or "kind/name" against both
+fn owner_matches(pod: &Value, filter: &str) -> bool {
+    let Some((kind, name)) = pod_owner(pod) else {
+        return false;
+    };
+    
+    match filter.split_once('/') {
+        Some((want_kind, want_name)) => kind.eq_ignore_ascii_case(want_kind) && name == want_name,
+        None => name == filter,
+    }
+}
+
+// Runs a read-only kubectl command and captures its output. Short-lived
+// exec-plugin/OIDC tokens can expire mid-session; kubectl refreshes them on the
+// next invocation, so an Unauthorized failure is retried once.
+fn run_kubectl(args: &[&str]) -> Result<std::process::Output> {
+    let output = kubectl(args).output()?;
+    
+    if !output.status.success() && is_auth_expired(&String::from_utf8_lossy(&output.stderr)) {
+        eprintln!("{}", "re-authenticating...".dimmed());
+        return Ok(kubectl(args).output()?);
+    }
+    
+    Ok(output)
+}
+
+fn is_auth_expired(stderr: &str) -> bool {
+    stderr.contains("Unauthorized") || stderr.contains("status code 401") || stderr.contains("(401)")
+}
+
+fn fetch_pods(namespace: &Option<String>, selector: Option<&str>) -> Result<Vec<Value>> {
+    if let Some(ns) = namespace
+        && is_namespace_set(ns)
+    {
+        let mut pods = Vec::new();
+        for ns in expand_namespaces(ns)? {
+            pods.extend(fetch_pods(&Some(ns), selector)?);
+        }
+        return Ok(pods);
+    }
+    
+    let mut args = vec!["get", "pods"];
+    
+    if let Some(ns) = namespace {
+        args.extend(&["-n", ns]);
+    } else {
+        args.push("--all-namespaces");
+    }
+    
+    if let Some(sel) = selector {
+        args.extend(&["-l", sel]);
+    }
+    
+    args.extend(&["-o", "json"]);
+    
+    let output = run_kubectl(&args)?;
+    
+    if !output.status.success() {
+        anyhow::bail!("kubectl get pods failed: {}", String::from_utf8_lossy(&output.stderr).trim());
+    }
+    
+    let json: Value = serde_json::from_slice(&output.stdout)?;
+    Ok(json["items"].as_array().cloned().unwrap_or_default())
+}
+
+struct PodRef {
+    name: String,
+    namespace: String,
+}
+
+// Longest selector preview before the rest is summarized
+const SELECTOR_PREVIEW_LIMIT: usize = 20;
+
+// Shared by every --selector feature: resolves the selector and shows what it
+// matched before anything runs against the pods
+fn resolve_selector(selector: &str, namespace: &Option<String>) -> Result<Vec<PodRef>> {
+    let pods: Vec<PodRef> = fetch_pods(namespace, Some(selector))?
+        .iter()
+        .map(|pod| {
+            let (name, namespace) = pod_name_ns(pod);
+            PodRef { name, namespace }
+        })
+        .collect();
+    
+    if pods.is_empty() {
+        return Err(KdbgError::NoSelectorMatch {
+            selector: selector.to_string(),
+            namespace: namespace.clone(),
+        }.into());
+    }
+    
+    println!("{} Selector '{}' matched {} pods:", "[INFO]".accent(), selector, pods.len());
+    for pod in pods.iter().take(SELECTOR_PREVIEW_LIMIT) {
+        println!("  - {} (namespace: {})", pod.name.accent(), namespace_colored(&pod.namespace, &pod.namespace));
+    }
+    if pods.len() > SELECTOR_PREVIEW_LIMIT {
+        println!("  ... and {} more", pods.len() - SELECTOR_PREVIEW_LIMIT);
+    }
+    
+    Ok(pods)
+}
+
+fn is_pod_ready(pod: &Value) -> bool {
+    pod["status"]["conditions"]
+        .as_array()
+        .map(|conds| conds.iter().any(|c| c["type"] == "Ready" && c["status"] == "True"))
+        .unwrap_or(false)
+}
+
+fn restart_count(pod: &Value) -> u64 {
+    pod["status"]["containerStatuses"]
+        .as_array()
+        .map(|cs| cs.iter().filter_map(|c| c["restartCount"].as_u64()).sum())
+        .unwrap_or(0)
+}
+
+fn parse_ordinal_pattern(pod_pattern: &str) -> Result<Option<String>> {
+    let Some((base, ordinal)) = pod_pattern.rsplit_once('#') else {
+        return Ok(None);
+    };
+    
+    match ordinal.parse::<u32>() {
+        Ok(ordinal) if !base.is_empty() => Ok(Some(format!("{}-{}", base, ordinal))),
+        _ => anyhow::bail!("'{}' isn't a StatefulSet ordinal; use NAME#N, e.g. web#2 for web-2", pod_pattern),
+    }
+}
+
+// Folds --ordinal into the pattern as NAME#N, refusing a pattern that already has one
+fn with_ordinal(pod_pattern: &str, ordinal: Option<u32>) -> Result<String> {
+    match ordinal {
+        Some(_) if pod_pattern.contains('#') => {
+            anyhow::bail!("'{}' already has an ordinal; use either NAME#N or --ordinal", pod_pattern)
+        }
+        Some(ordinal) => Ok(format!("{}#{}", pod_pattern, ordinal)),
+        None => Ok(pod_pattern.to_string()),
+    }
+}
+
+fn find_pod(pod_pattern: &str, namespace: Option<String>) -> Result<(String, String)> {
+    resolve_pod(pod_pattern, namespace).map(|pod| pod_name_ns(&pod))
+}
+
+fn pod_name_ns(pod: &Value) -> (String, String) {
+    let name = pod["metadata"]["name"].as_str().unwrap_or("unknown").to_string();
+    let ns = pod["metadata"]["namespace"].as_str().unwrap_or("default").to_string();
+    (name, ns)
+}
+
+fn pod_containers(pod: &Value) -> Vec<String> {
+    pod["spec"]["containers"]
+        .as_array()
+        .map(|cs| cs.iter().filter_map(|c| c["name"].as_str().map(String::from)).collect())
+        .unwrap_or_default()
+}
+
+fn pod_init_containers(pod: &Value) -> Vec<String> {
+    pod["spec"]["initContainers"]
+        .as_array()
+        .map(|cs| cs.iter().filter_map(|c| c["name"].as_str().map(String::from)).collect())
+        .unwrap_or_default()
+}
+
+// Failures that tooling may want to tell apart. Each variant has a stable
+// code for --error-format json; any other error is reported as "Error".
+#[derive(Debug, thiserror::Error)]
+enum KdbgError {
+    #[error("No pods found matching '{pattern}'")]
+    NoPodMatch { pattern: String, namespace: Option<String> },
+    #[error("Please be more specific")]
+    AmbiguousPodMatch { pattern: String, namespace: Option<String>, matches: Vec<String> },
+    #[error("No pods found matching selector '{selector}'")]
+    NoSelectorMatch { selector: String, namespace: Option<String> },
+    #[error("Metrics API not available")]
+    MetricsUnavailable,
+}
+
+fn error_json(error: &anyhow::Error) -> Value {
+    let message = error.to_string();
+    
+    match error.downcast_ref::<KdbgError>() {
+        Some(KdbgError::NoPodMatch { pattern, namespace }) => serde_json::json!({
+            "error": "NoPodMatch", "pattern": pattern, "namespace": namespace, "message": message,
+        }),
+        Some(KdbgError::AmbiguousPodMatch { pattern, namespace, matches }) => serde_json::json!({
+            "error": "AmbiguousPodMatch", "pattern": pattern, "namespace": namespace,
+            "matches": matches, "message": message,
+        }),
+        Some(KdbgError::NoSelectorMatch { selector, namespace }) => serde_json::json!({
+            "error": "NoSelectorMatch", "selector": selector, "namespace": namespace, "message": message,
+        }),
+        Some(KdbgError::MetricsUnavailable) => serde_json::json!({ "error": "MetricsUnavailable", "message": message }),
+        None => serde_json::json!({ "error": "Error", "message": message }),
+    }
+}
+
+// Like find_pod, but returns the full pod JSON for callers that need more than the name
+fn resolve_pod(pod_pattern: &str, namespace: Option<String>) -> Result<Value> {
+    // BASE#N names StatefulSet pod BASE-N exactly, where a substring would
+    // also match BASE-N0, BASE-N1, ...
+    let ordinal_name = parse_ordinal_pattern(pod_pattern)?;
+    match &ordinal_name {
+        Some(name) => explain(|| format!("Looking for the pod named '{}'", name)),
+        None => explain(|| format!("Looking for pods whose name contains '{}'", pod_pattern)),
+    }
+    let pods = fetch_pods(&namespace, None)?;
+    
+    let matches: Vec<_> = pods.iter()
+        .filter(|pod| {
+            let name = pod["metadata"]["name"].as_str().unwrap_or("");
+            match &ordinal_name {
+                Some(wanted) => name == wanted,
+                None => name.contains(pod_pattern),
+            }
+        })
+        .collect();
+    
+    if matches.is_empty() {
+        if let Some(name) = &ordinal_name {
+            eprintln!("{} '{}' means the StatefulSet pod named exactly {}", "[INFO]".accent(), pod_pattern, name);
+        }
+        return Err(KdbgError::NoPodMatch { pattern: pod_pattern.to_string(), namespace }.into());
+    }
+    
+    if matches.len() > 1 {
+        println!("{} Multiple pods found:", "[INFO]".warning());
+        for pod in &matches {
+            let name = pod["metadata"]["name"].as_str().unwrap_or("unknown");
+            let ns = pod["metadata"]["namespace"].as_str().unwrap_or("default");
+            let phase = pod_phase(pod);
+            let age = pod["metadata"]["creationTimestamp"].as_str().map(calculate_age).unwrap_or_default();
+            println!("  - {} (namespace: {}) {} {}", 
+                name.accent(), namespace_colored(ns, ns), phase_colored(phase, phase), age.muted());
+        }
+        return Err(KdbgError::AmbiguousPodMatch {
+            pattern: pod_pattern.to_string(),
+            namespace,
+            matches: matches.iter().map(|pod| pod_name_ns(pod).0).collect(),
+        }.into());
+    }
+    
+    explain(|| {
+        let (name, ns) = pod_name_ns(matches[0]);
+        format!("Found 1 match: {} in {}", name, ns)
+    });
+    
+    Ok(matches[0].clone())
+}
+
+fn show_logs(logs: &LogsArgs) -> Result<()> {
+    if logs.tail_file.is_some() && !(logs.follow || logs.follow_restarts) {
+        anyhow::bail!("--tail-file needs --follow or --follow-restarts");
+    }
+    if logs.tail_lines == 0 {
+        anyhow::bail!("--tail-lines must be at least 1");
+    }
+    
+    let all_containers = all_log_containers(logs);
+    if all_containers && (logs.all_previous || logs.follow_restarts || logs.resume) {
+        anyhow::bail!("All-container logs can't be combined with --all-previous, --follow-restarts or --resume; pick a container with -c");
+    }
+    
+    if let Some(job) = &logs.job {
+        return show_job_logs(logs, job);
+    }
+    
+    let pod_pattern = logs.pod.as_deref().unwrap_or_default();
+    let pattern = with_ordinal(pod_pattern, logs.ordinal)?;
+    let pod = resolve_pod(&pattern, namespace_arg(logs.namespace.clone()))?;
+    let (pod_name, ns) = pod_name_ns(&pod);
+    let container = if all_containers { None } else { log_container(&pod, logs)? };
+    
+    let mut resume = logs.resume.then(|| {
+        let name = container.clone().or_else(|| default_container_name(&pod)).unwrap_or_default();
+        LogResume::load(&format!("{}/{}/{}/{}", default_context().unwrap_or(""), ns, pod_name, name))
+    });
+    let since_arg = resume.as_ref().and_then(LogResume::since_arg);
+    
+    let tail_str = logs.tail.to_string();
+    let limit_bytes = limit_bytes_arg(logs);
+    let mut args = vec!["logs", &pod_name, "-n", &ns];
+    match &since_arg {
+        Some(since_arg) => args.push(since_arg),
+        None => args.extend(&["--tail", &tail_str]),
+    }
+    if let Some(container) = &container {
+        args.extend(&["-c", container]);
+    }
+    if all_containers {
+        args.extend(&["--all-containers", "--prefix"]);
+    }
+    if resume.is_some() {
+        args.push("--timestamps");
+    }
+    args.extend(limit_bytes.as_deref());
+    args.extend(extra_kubectl_args());
+    
+    if logs.interactive {
+        return page_logs(&args, log_line_format(logs));
+    }
+    
+    println!("{} Logs for pod: {} (namespace: {})", 
+        "[INFO]".accent(), pod_name.bold(), ns.muted());
+    if !logs.quiet {
+        let containers = if all_containers { pod_containers(&pod) } else { container.iter().cloned().collect() };
+        let statuses: Vec<&Value> = match containers.as_slice() {
+            [] => container_status(&pod, None).into_iter().collect(),
+            names => names.iter().filter_map(|name| container_status(&pod, Some(name))).collect(),
+        };
+        for status in statuses {
+            println!("{} {}", "[INFO]".accent(), container_status_line(status));
+        }
+    }
+    if let Some(since) = resume.as_ref().and_then(|r| r.last.as_deref()) {
+        println!("{} Resuming after {}, the last line the previous run printed", "[INFO]".accent(), since);
+    }
+    println!("{}", "-".repeat(100));
+    
+    if logs.follow_restarts {
+        return follow_across_restarts(&pod, logs, container.as_deref());
+    }
+    
+    if logs.all_previous {
+        return show_all_previous_logs(&pod, logs, container.as_deref());
+    }
+    
+    if logs.follow {
+        args.push("-f");
+    }
+    
+    let mut tail_file = logs.tail_file.as_ref().map(|path| TailFile::new(path, logs.tail_lines));
+    let mut dedup = LogDedup::from_args(logs);
+    
+    let status = if logs.pretty_json || all_containers || tail_file.is_some() || resume.is_some() || dedup.is_some() {
+        stream_log_lines(&args, log_line_format(logs), tail_file.as_mut(), resume.as_mut(), dedup.as_mut())?
+    } else {
+        kubectl(&args).status()?
+    };
+    
+    if !status.success() {
+        anyhow::bail!("Failed to get logs");
+    }
+    
+    Ok(())
+}
+
+// The kubelet keeps only the current and the one previous container instance,
+// so that is the most that can be recovered; older instances are reported as
+// lost rather than silently missing
+fn show_all_previous_logs(pod: &Value, logs: &LogsArgs, container: Option<&str>) -> Result<()> {
+    let (pod_name, ns) = pod_name_ns(pod);
+    let status = container_status(pod, container)
+        .ok_or_else(|| anyhow::anyhow!("Pod {} has no container status yet", pod_name))?;
+    let container = status["name"].as_str().unwrap_or("unknown");
+    let restarts = status["restartCount"].as_u64().unwrap_or(0);
+    
+    let started = status["state"]["running"]["startedAt"].as_str()
+        .or_else(|| status["state"]["terminated"]["startedAt"].as_str())
+        .unwrap_or("unknown");
+    println!("\n{} current instance (container {}, restart #{}, started {})", 
+        "==>".accent().bold(), container.bold(), restarts, started);
+    let limit_bytes = limit_bytes_arg(logs);
+    let mut args = vec!["logs", &pod_name, "-n", &ns, "-c", container];
+    args.extend(limit_bytes.as_deref());
+    args.extend(extra_kubectl_args());
+    print_container_logs(&args, logs.pretty_json)?;
+    
+    if restarts == 0 {
+        println!("\n{} Container has never restarted, there is no previous instance", "[INFO]".accent());
+        return Ok(());
+    }
+    
+    let last = &status["lastState"]["terminated"];
+    let exit = match last["exitCode"].as_i64() {
+        Some(code) => format!("exit {} ({})", code, last["reason"].as_str().unwrap_or("")),
+        None => "exit unknown".to_string(),
+    };
+    println!("\n{} previous instance (container {}, restart #{}, terminated {}, {})", 
+        "==>".accent().bold(), container.bold(), restarts - 1,
+        last["finishedAt"].as_str().unwrap_or("unknown"), exit.danger());
+    args.push("--previous");
+    print_container_logs(&args, logs.pretty_json)?;
+    
+    if restarts > 1 {
+        println!("\n{} Logs of the {} earlier instance(s) are gone: the kubelet keeps only one previous instance", 
+            "[WARN]".warning(), restarts - 1);
+        println!("{} Run `kdbg watch {}` to save each crash's logs as it happens", "[INFO]".accent(), pod_name);
+    }
+    
+    Ok(())
+}
+
+// kubectl applies --limit-bytes on the server, after --tail picked the lines
+fn limit_bytes_arg(logs: &LogsArgs) -> Option<String> {
+    logs.limit_bytes.map(|bytes| format!("--limit-bytes={}", bytes))
+}
+
+fn print_container_logs(args: &[&str], pretty_json: bool) -> Result<()> {
+    let status = if pretty_json {
+        stream_log_lines(args, pretty_json_line, None, None, None)?
+    } else {
+        kubectl(args).status()?
+    };
+    
+    if !status.success() {
+        eprintln!("{} Failed to get logs ({})", "[ERROR]".danger(), args.join(" "));
+    }
+    
+    Ok(())
+}
+
+// kubectl logs -f ends when its pod goes away; re-resolve the pod through its
+// controller (or its name, for bare pods) and carry on with the replacement
+fn follow_across_restarts(pod: &Value, logs: &LogsArgs, container: Option<&str>) -> Result<()> {
+    let (mut pod_name, ns) = pod_name_ns(pod);
+    let mut uid = pod["metadata"]["uid"].as_str().unwrap_or("").to_string();
+    let owner = pod_owner(pod);
+    let tail_str = logs.tail.to_string();
+    let limit_bytes = limit_bytes_arg(logs);
+    let mut since: Option<String> = None;
+    let mut tail_file = logs.tail_file.as_ref().map(|path| TailFile::new(path, logs.tail_lines));
+    let mut dedup = LogDedup::from_args(logs);
+    let mut failures = 0;
+    let mut streamed = false;
+    
+    loop {
+        let since_arg = since.as_ref().map(|t| format!("--since-time={}", t));
+        let mut args = vec!["logs", &pod_name, "-n", &ns, "-f"];
+        if let Some(container) = container {
+            args.extend(&["-c", container]);
+        }
+        args.extend(limit_bytes.as_deref());
+        match &since_arg {
+            Some(since_arg) => args.push(since_arg),
+            None => args.extend(&["--tail", &tail_str]),
+        }
+        args.extend(extra_kubectl_args());
+        
+        let status = if logs.pretty_json || tail_file.is_some() || dedup.is_some() {
+            stream_log_lines(&args, log_line_format(logs), tail_file.as_mut(), None, dedup.as_mut())?
+        } else {
+            kubectl(&args).status()?
+        };
+        // A bad -c or missing RBAC fails the same way on every retry
+        if status.success() {
+            failures = 0;
+            streamed = true;
+        } else {
+            failures += 1;
+            if !streamed {
+                anyhow::bail!("Failed to get logs for {}", pod_name);
+            }
+            if failures == FOLLOW_FAILURE_LIMIT {
+                anyhow::bail!("Failed to get logs for {} {} times in a row, giving up", pod_name, failures);
+            }
+        }
+        let ended = chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Secs, true);
+        
+        let next = wait_for_replacement(&pod_name, &ns, owner.as_ref(), &uid)?;
+        let (next_name, _) = pod_name_ns(&next);
+        let next_uid = next["metadata"]["uid"].as_str().unwrap_or("").to_string();
+        
+        if next_uid == uid {
+            // Same pod (stream dropped or container restarted): resume where it left off
+            since = Some(ended);
+        } else {
+            println!("\n{} pod replaced, following {}", "[INFO]".warning(), next_name.bold());
+            println!("{}", "-".repeat(100));
+            since = None;
+        }
+        
+        pod_name = next_name;
+        uid = next_uid;
+    }
+}
+
+fn wait_for_replacement(pod_name: &str, ns: &str, owner: Option<&(String, String)>, uid: &str) -> Result<Value> {
+    let namespace = Some(ns.to_string());
+    let mut announced = false;
+    
+    loop {
+        std::thread::sleep(std::time::Duration::from_secs(2));
+        
+        let pods = fetch_pods(&namespace, None)?;
+        let mut candidates: Vec<&Value> = pods.iter()
+            .filter(|pod| match owner {
+                Some(owner) => pod_owner(pod).as_ref() == Some(owner),
+                None => pod["metadata"]["name"].as_str() == Some(pod_name),
+            })
+            .filter(|pod| pod["metadata"]["deletionTimestamp"].is_null())
+            .filter(|pod| pod["status"]["phase"].as_str() == Some("Running"))
+            .collect();
+        
+        // Stay on the current pod while it lives, otherwise take the newest
+        candidates.sort_by_key(|pod| {
+            let same = pod["metadata"]["uid"].as_str() == Some(uid);
+            (same, pod["metadata"]["creationTimestamp"].as_str().unwrap_or("").to_string())
+        });
+        if let Some(pod) = candidates.pop() {
+            return Ok(pod.clone());
+        }
+        
+        // Nothing recreates a bare pod, so once it's deleted there is nothing to wait for
+        let bare_pod_gone = owner.is_none() && !pods.iter().any(|pod| {
+            pod["metadata"]["name"].as_str() == Some(pod_name) && pod["metadata"]["deletionTimestamp"].is_null()
+        });
+        if bare_pod_gone {
+            anyhow::bail!("Pod {} is gone and has no controller to replace it", pod_name);
+        }
+        
+        if !announced {
+            println!("\n{} {} is gone, waiting for a replacement...", "[INFO]".warning(), pod_name);
+            announced = true;
+        }
+    }
+}
+
+// -c as given, except that `init` (or --init) means the pod's init container,
+// unless the pod really has a container called init
+fn log_container(pod: &Value, logs: &LogsArgs) -> Result<Option<String>> {
+    let wants_init = logs.init || logs.container.as_deref() == Some("init");
+    let (pod_name, _) = pod_name_ns(pod);
+    let init_containers = pod_init_containers(pod);
+    
+    if !wants_init {
+        return Ok(logs.container.clone());
+    }
+    // A container actually named init is what both --init and -c init mean
+    if pod_containers(pod).iter().chain(&init_containers).any(|c| c == "init") {
+        return Ok(Some("init".to_string()));
+    }
+    
+    match init_containers.as_slice() {
+        [] => anyhow::bail!("Pod {} has no init containers", pod_name),
+        [only] => Ok(Some(only.clone())),
+        _ => pick_container(&pod_name, &init_containers).map(Some),
+    }
+}
+
+// Asks for a number on the terminal; without one, the choices go into the error
+fn pick_container(pod_name: &str, containers: &[String]) -> Result<String> {
+    use std::io::{IsTerminal, Write};
+    
+    if !std::io::stdin().is_terminal() {
+        anyhow::bail!("Pod {} has {} init containers ({}); pick one with -c", 
+            pod_name, containers.len(), containers.join(", "));
+    }
+    
+    println!("{} Pod {} has {} init containers:", "[?]".warning(), pod_name.bold(), containers.len());
+    for (i, name) in containers.iter().enumerate() {
+        println!("  {}) {}", i + 1, name.accent());
+    }
+    print!("{} Container [1-{}]: ", "[?]".warning(), containers.len());
+    std::io::stdout().flush()?;
+    
+    let mut answer = String::new();
+    std::io::stdin().read_line(&mut answer)?;
+    
+    answer.trim()
+        .parse::<usize>()
+        .ok()
+        .and_then(|n| containers.get(n.checked_sub(1)?))
+        .cloned()
+        .ok_or_else(|| anyhow::anyhow!("No container picked"))
+}
+
+// A named container's status, init containers included; the default container's without a name
+fn container_status<'a>(pod: &'a Value, container: Option<&str>) -> Option<&'a Value> {
+    let Some(name) = container else {
+        return default_container_status(pod);
+    };
+    
+    ["containerStatuses", "initContainerStatuses"]
+        .iter()
+        .filter_map(|key| pod["status"][key].as_array())
+        .flatten()
+        .find(|c| c["name"].as_str() == Some(name))
+}
+
+// The container kubectl logs picks without -c: the default-container
+// annotation if set, otherwise the first container in the spec
+fn default_container_status(pod: &Value) -> Option<&Value> {
+    let name = default_container_name(pod)?;
+    
+    pod["status"]["containerStatuses"]
+        .as_array()?
+        .iter()
+        .find(|c| c["name"].as_str() == Some(&name))
+}
+
+fn default_container_name(pod: &Value) -> Option<String> {
+    pod["metadata"]["annotations"]["kubectl.kubernetes.io/default-container"]
+        .as_str()
+        .or_else(|| pod["spec"]["containers"][0]["name"].as_str())
+        .map(String::from)
+}
+
+// e.g. "Container app: running, ready, 12 restarts, last exit 137 (OOMKilled)"
+fn container_status_line(status: &Value) -> String {
+    let name = status["name"].as_str().unwrap_or("unknown");
+    let state = &status["state"];
+    
+    let state_colored = if state["running"].is_object() {
+        "running".success()
+    } else if let Some(waiting) = state["waiting"].as_object() {
+        let reason = waiting.get("reason").and_then(|r| r.as_str()).unwrap_or("waiting");
+        format!("waiting ({})", reason).warning()
+    } else if let Some(terminated) = state["terminated"].as_object() {
+        let reason = terminated.get("reason").and_then(|r| r.as_str()).unwrap_or("terminated");
+        format!("terminated ({})", reason).danger()
+    } else {
+        "unknown".normal()
+    };
+    
+    let ready = if status["ready"].as_bool().unwrap_or(false) { "ready".success() } else { "not ready".danger() };
+    
+    let restarts = status["restartCount"].as_u64().unwrap_or(0);
+    let restarts_colored = RestartThresholds::load(None).colored(restarts, &format!("{} restarts", restarts));
+    
+    let mut line = format!("Container {}: {}, {}, {}", name.bold(), state_colored, ready, restarts_colored);
+    
+    let last = &status["lastState"]["terminated"];
+    if let Some(code) = last["exitCode"].as_i64() {
+        let reason = last["reason"].as_str().unwrap_or("");
+        line.push_str(&format!(", last exit {} ({})", code, reason).danger().to_string());
+    }
+    
+    line
+}
+
+// Runs kubectl with stdout piped so each log line can be rewritten before printing
+fn stream_log_lines(
+    args: &[&str],
+    format_line: fn(&str) -> String,
+    mut tail_file: Option<&mut TailFile>,
+    mut resume: Option<&mut LogResume>,
+    mut dedup: Option<&mut LogDedup>,
+) -> Result<std::process::ExitStatus> {
+    use std::io::{BufRead, BufReader};
+    
+    let mut child = kubectl(args)
+        .stdout(Stdio::piped())
+        .spawn()?;
+    
+    if let Some(stdout) = child.stdout.take() {
+        let mut reader = BufReader::new(stdout);
+        let mut line = String::new();
+        
+        while reader.read_line(&mut line)? > 0 {
+            let text = line.trim_end_matches(['\n', '\r']);
+            let text = match resume.as_deref_mut() {
+                Some(resume) => match resume.observe(text) {
+                    Some(text) => text,
+                    None => {
+                        line.clear();
+                        continue;
+                    }
+                },
+                None => text,
+            };
+            match dedup.as_deref_mut() {
+                Some(dedup) => dedup.push(text, format_line),
+                None => println!("{}", format_line(text)),
+            }
+            
+            if let Some(tail) = tail_file.as_deref_mut() {
+                // An empty buffer means we've caught up with the stream
+                tail.push(text, reader.buffer().is_empty())?;
+            }
+            line.clear();
+        }
+    }
+    
+    if let Some(tail) = tail_file {
+        tail.flush()?;
+    }
+    if let Some(resume) = resume {
+        resume.save();
+    }
+    if let Some(dedup) = dedup {
+        dedup.flush();
+    }
+    
+    Ok(child.wait()?)
+}
+
+// --dedup: a line matching one of the last `window` distinct lines is counted
+// against it instead of printed again. On a terminal the earlier line is
+// rewritten in place as "line (xN)"; otherwise lines are held until they
+// leave the window (or the stream ends) and printed once with their count,
+// like uniq -c
+struct LogDedup<W: std::io::Write = std::io::Stdout> {
+    window: usize,
+    // Terminal rows and columns when counts are updated in place
+    screen: Option<(usize, usize)>,
+    recent: std::collections::VecDeque<DedupLine>,
+    out: W,
+}
+
+struct DedupLine {
+    text: String,
+    formatted: String,
+    count: u64,
+    // Terminal rows the line took when last printed
+    rows: usize,
+}
+
+impl LogDedup {
+    fn from_args(logs: &LogsArgs) -> Option<LogDedup> {
+        use std::io::IsTerminal;
+        
+        logs.dedup.then(|| LogDedup {
+            window: logs.dedup_window.unwrap_or(1) as usize,
+            screen: std::io::stdout().is_terminal().then(terminal_size).flatten(),
+            recent: Default::default(),
+            out: std::io::stdout(),
+        })
+    }
+}
+
+impl<W: std::io::Write> LogDedup<W> {
+    fn push(&mut self, text: &str, format_line: fn(&str) -> String) {
+        if let Some(i) = self.recent.iter().position(|line| line.text == text) {
+            self.recent[i].count += 1;
+            if self.screen.is_none() || self.rewrite(i) {
+                return;
+            }
+            // Scrolled off or grew a row: start over below
+            self.recent.remove(i);
+        }
+        
+        if self.recent.len() == self.window {
+            let oldest = self.recent.pop_front().unwrap();
+            if self.screen.is_none() {
+                let _ = writeln!(self.out, "{}", oldest.display());
+            }
+        }
+        
+        let mut line = DedupLine { text: text.to_string(), formatted: format_line(text), count: 1, rows: 1 };
+        if let Some((_, cols)) = self.screen {
+            line.rows = screen_rows(&line.formatted, cols);
+            let _ = writeln!(self.out, "{}", line.formatted);
+        }
+        self.recent.push_back(line);
+    }
+    
+    // Redraws recent[i] with its new count where it was printed, keeping the
+    // cursor below the last line; false when that spot can't be reached
+    fn rewrite(&mut self, i: usize) -> bool {
+        let Some((rows, cols)) = self.screen else {
+            return false;
+        };
+        let text = self.recent[i].display();
+        let up: usize = self.recent.iter().skip(i).map(|line| line.rows).sum();
+        if up > rows || screen_rows(&text, cols) != self.recent[i].rows {
+            return false;
+        }
+        
+        // The count only makes the line longer, so it covers what was there
+        let down = up - (self.recent[i].rows - 1);
+        let _ = write!(self.out, "\x1b[{}A\r{}\x1b[{}B\r", up, text, down);
+        let _ = self.out.flush();
+        true
+    }
+    
+    fn flush(&mut self) {
+        if self.screen.is_none() {
+            for line in self.recent.drain(..) {
+                let _ = writeln!(self.out, "{}", line.display());
+            }
+        }
+    }
+}
+
+impl DedupLine {
+    fn display(&self) -> String {
+        match self.count {
+            1 => self.formatted.clone(),
+            n => format!("{} {}", self.formatted, format!("(x{})", n).muted()),
+        }
+    }
+}
+
+// Rows `text` fills on a terminal `cols` wide
+fn screen_rows(text: &str, cols: usize) -> usize {
+    text.split('\n')
+        .map(|line| display_width(&strip_ansi(line)).div_ceil(cols.max(1)).max(1))
+        .sum()
+}
+
+// Where a --resume run left off, per context/namespace/pod/container. Lines
+// come with --timestamps; the timestamp is stripped before printing and the
+// newest one is saved (at most once a second, and when the stream ends), so
+// an interrupted session picks up from there with --since-time
+struct LogResume {
+    key: String,
+    // Lines up to here were printed before; --since-time is only second-precise
+    seen_until: Option<chrono::DateTime<chrono::FixedOffset>>,
+    last: Option<String>,
+    saved: std::time::Instant,
+}
+
+impl LogResume {
+    fn load(key: &str) -> LogResume {
+        let last = load_log_positions().get(key).and_then(Value::as_str).map(String::from);
+        LogResume {
+            key: key.to_string(),
+            seen_until: last.as_deref().and_then(|ts| chrono::DateTime::parse_from_rfc3339(ts).ok()),
+            last,
+            saved: std::time::Instant::now(),
+        }
+    }
+    
+    fn since_arg(&self) -> Option<String> {
+        self.last.as_ref().map(|ts| format!("--since-time={}", ts))
+    }
+    
+    // The line without its timestamp, or None when an earlier run printed it already
+    fn observe<'a>(&mut self, line: &'a str) -> Option<&'a str> {
+        let Some((ts, text)) = line.split_once(' ') else {
+            return Some(line);
+        };
+        let Ok(time) = chrono::DateTime::parse_from_rfc3339(ts) else {
+            return Some(line);
+        };
+        if self.seen_until.is_some_and(|seen| time <= seen) {
+            return None;
+        }
+        
+        self.last = Some(ts.to_string());
+        if self.saved.elapsed() >= std::time::Duration::from_secs(1) {
+            self.save();
+        }
+        Some(text)
+    }
+    
+    // Best effort like the shell cache: a lost position only means re-reading --tail
+    fn save(&mut self) {
+        self.saved = std::time::Instant::now();
+        let (Some(path), Some(last)) = (log_positions_path(), &self.last) else {
+            return;
+        };
+        let mut positions = load_log_positions();
+        positions.insert(self.key.clone(), Value::from(last.as_str()));
+        
+        if let Some(dir) = path.parent() {
+            let _ = std::fs::create_dir_all(dir);
+        }
+        let _ = std::fs::write(&path, Value::Object(positions).to_string());
+    }
+}
+
+fn log_positions_path() -> Option<PathBuf> {
+    state_dir().map(|dir| dir.join("log-positions.json"))
+}
+
+fn load_log_positions() -> serde_json::Map<String, Value> {
+    log_positions_path()
+        .and_then(|path| std::fs::read_to_string(path).ok())
+        .and_then(|text| serde_json::from_str(&text).ok())
+        .unwrap_or_default()
+}
+
+fn log_line_format(logs: &LogsArgs) -> fn(&str) -> String {
+    match (all_log_containers(logs), logs.pretty_json) {
+        (true, true) => |line| prefixed_log_line(line, pretty_json_line),
+        (true, false) => |line| prefixed_log_line(line, str::to_string),
+        (false, true) => pretty_json_line,
+        (false, false) => str::to_string,
+    }
+}
+
+// A Job's pods come and go with retries and parallelism, so they're found
+// through the Job's own selector, and kubectl streams them all at once
+fn show_job_logs(logs: &LogsArgs, job_name: &str) -> Result<()> {
+    let namespace = namespace_arg(logs.namespace.clone());
+    single_namespace(&namespace)?;
+    
+    let job = find_job(job_name, &namespace)?;
+    let ns = job["metadata"]["namespace"].as_str().unwrap_or("default").to_string();
+    let mut selector = job["spec"]["selector"]["matchLabels"].as_object().into_iter().flatten()
+        .map(|(key, value)| format!("{}={}", key, value.as_str().unwrap_or("")))
+        .collect::<Vec<_>>()
+        .join(",");
+    if selector.is_empty() {
+        // The Job controller labels its pods with this even when the selector was hand-written
+        selector = format!("job-name={}", job_name);
+    }
+    
+    let pods = fetch_pods(&Some(ns.clone()), Some(&selector))?;
+    let status = &job["status"];
+    println!("{} Logs for job: {} (namespace: {}): {} active, {} succeeded, {} failed", 
+        "[INFO]".accent(), job_name.bold(), ns.muted(),
+        status["active"].as_u64().unwrap_or(0), status["succeeded"].as_u64().unwrap_or(0), status["failed"].as_u64().unwrap_or(0));
+    if pods.is_empty() {
+        anyhow::bail!("Job {} has no pods left (finished Jobs' pods may have been cleaned up)", job_name);
+    }
+    if !logs.quiet {
+        for pod in pods.iter().take(SELECTOR_PREVIEW_LIMIT) {
+            let (name, _) = pod_name_ns(pod);
+            let phase = pod_phase(pod);
+            let reason = pod_reason(pod).map(|r| format!(" ({})", r)).unwrap_or_default();
+            println!("  - {} {}{}", name.accent(), phase_colored(phase, phase), reason);
+        }
+        if pods.len() > SELECTOR_PREVIEW_LIMIT {
+            println!("  ... and {} more", pods.len() - SELECTOR_PREVIEW_LIMIT);
+        }
+    }
+    println!("{}", "-".repeat(100));
+    
+    let tail_str = logs.tail.to_string();
+    let limit_bytes = limit_bytes_arg(logs);
+    // kubectl follows at most 5 pods at once unless told otherwise
+    let max_requests = format!("--max-log-requests={}", pods.len().max(5));
+    let mut args = vec!["logs", "-l", &selector, "-n", &ns, "--tail", &tail_str, "--prefix"];
+    match logs.container.as_deref() {
+        _ if all_log_containers(logs) => args.push("--all-containers"),
+        Some(container) => args.extend(&["-c", container]),
+        None => {}
+    }
+    if logs.follow {
+        args.extend(&["-f", &max_requests]);
+    }
+    args.extend(limit_bytes.as_deref());
+    args.extend(extra_kubectl_args());
+    
+    let format_line = job_log_line_format(logs);
+    if logs.interactive {
+        return page_logs(&args, format_line);
+    }
+    
+    let mut tail_file = logs.tail_file.as_ref().map(|path| TailFile::new(path, logs.tail_lines));
+    let mut dedup = LogDedup::from_args(logs);
+    if !stream_log_lines(&args, format_line, tail_file.as_mut(), None, dedup.as_mut())?.success() {
+        anyhow::bail!("Failed to get logs");
+    }
+    
+    Ok(())
+}
+
+// Exact name: Job names are chosen by people, not generated
+fn find_job(name: &str, namespace: &Option<String>) -> Result<Value> {
+    let mut args = vec!["get", "jobs", "-o", "json"];
+    match namespace {
+        Some(ns) => args.extend(&["-n", ns]),
+        None => args.push("--all-namespaces"),
+    }
+    
+    let output = run_kubectl(&args)?;
+    if !output.status.success() {
+        anyhow::bail!("Failed to get jobs: {}", String::from_utf8_lossy(&output.stderr).trim());
+    }
+    let json: Value = serde_json::from_slice(&output.stdout)?;
+    let mut jobs: Vec<Value> = json["items"].as_array().cloned().unwrap_or_default()
+        .into_iter()
+        .filter(|job| job["metadata"]["name"] == name)
+        .collect();
+    
+    match jobs.len() {
+        0 => anyhow::bail!("No job named '{}'{}", name, namespace.as_ref().map(|ns| format!(" in namespace {}", ns)).unwrap_or_default()),
+        1 => Ok(jobs.remove(0)),
+        _ => {
+            println!("{} Job {} exists in several namespaces:", "[INFO]".warning(), name);
+            for job in &jobs {
+                println!("  - {}", job["metadata"]["namespace"].as_str().unwrap_or("").accent());
+            }
+            anyhow::bail!("Pick one with -n");
+        }
+    }
+}
+
+fn job_log_line_format(logs: &LogsArgs) -> fn(&str) -> String {
+    match (all_log_containers(logs), logs.pretty_json) {
+        (true, true) => |line| job_log_line(line, true, pretty_json_line),
+        (true, false) => |line| job_log_line(line, true, str::to_string),
+        (false, true) => |line| job_log_line(line, false, pretty_json_line),
+        (false, false) => |line| job_log_line(line, false, str::to_string),
+    }
+}
+
+// "[pod/NAME/CONTAINER] line" from kubectl --prefix becomes "[NAME] line",
+// or "[NAME/CONTAINER] line" when every container is shown
+fn job_log_line(line: &str, with_container: bool, format_line: fn(&str) -> String) -> String {
+    let Some((source, text)) = line.strip_prefix("[pod/").and_then(|rest| rest.split_once("] ")) else {
+        return format_line(line);
+    };
+    let source = if with_container { source } else { source.split('/').next().unwrap_or(source) };
+    
+    format!("{} {}", format!("[{}]", source).accent(), format_line(text))
+}
+
+// Container names are DNS labels, so a literal `*` can't clash with a real one
+fn all_log_containers(logs: &LogsArgs) -> bool {
+    logs.all_containers || logs.container.as_deref() == Some("*")
+}
+
+// kubectl --prefix writes "[pod/NAME/CONTAINER] line"; the pod is already in
+// the header, so only the container is kept
+fn prefixed_log_line(line: &str, format_line: fn(&str) -> String) -> String {
+    let Some((source, text)) = line.strip_prefix("[pod/").and_then(|rest| rest.split_once("] ")) else {
+        return format_line(line);
+    };
+    let container = source.rsplit('/').next().unwrap_or(source);
+    
+    format!("{} {}", format!("[{}]", container).accent(), format_line(text))
+}
+
+// The last N lines of a followed log, mirrored to disk so recent context
+// outlives the terminal's scrollback. Lines are stored as received (no colors).
+struct TailFile {
+    path: PathBuf,
+    lines: std::collections::VecDeque<String>,
+    capacity: usize,
+    written: std::time::Instant,
+}
+
+impl TailFile {
+    fn new(path: &std::path::Path, capacity: usize) -> TailFile {
+        TailFile {
+            path: path.to_path_buf(),
+            lines: std::collections::VecDeque::with_capacity(capacity),
+            capacity,
+            written: std::time::Instant::now(),
+        }
+    }
+    
+    // Rewrites the file once caught up, and at most every 250ms during a
+    // burst, so a chatty pod doesn't turn into a rewrite per line
+    fn push(&mut self, line: &str, caught_up: bool) -> Result<()> {
+        if self.lines.len() == self.capacity {
+            self.lines.pop_front();
+        }
+        self.lines.push_back(line.to_string());
+        
+        if caught_up || self.written.elapsed() >= std::time::Duration::from_millis(250) {
+            self.flush()?;
+        }
+        
+        Ok(())
+    }
+    
+    // Written to a temp file and renamed, so readers never see a half-written window
+    fn flush(&mut self) -> Result<()> {
+        let mut text = String::new();
+        for line in &self.lines {
+            text.push_str(line);
+            text.push('\n');
+        }
+        
+        let tmp = PathBuf::from(format!("{}.tmp", self.path.display()));
+        std::fs::write(&tmp, text)?;
+        std::fs::rename(&tmp, &self.path)?;
+        self.written = std::time::Instant::now();
+        
+        Ok(())
+    }
+}
+
+fn page_logs(args: &[&str], format_line: fn(&str) -> String) -> Result<()> {
+    use std::io::IsTerminal;
+    
+    let output = kubectl(args)
+        .stderr(Stdio::inherit())
+        .output()?;
+    
+    if !output.status.success() {
+        anyhow::bail!("Failed to get logs");
+    }
+    
+    let text = String::from_utf8_lossy(&output.stdout);
+    
+    if !std::io::stdout().is_terminal() || !std::io::stdin().is_terminal() {
+        print!("{}", text);
+        return Ok(());
+    }
+    
+    // Colored like the unpaged output (unless --no-color/NO_COLOR); the pager passes the codes through
+    let lines: Vec<String> = text
+        .lines()
+        .map(format_line)
+        .collect();
+    
+    Pager::new(lines).run()
+}
+
+// Minimal full-screen pager on crossterm's raw mode and key events, drawn
+// with ANSI escapes. Lines may carry color codes; searching works on their plain text.
+struct Pager {
+    lines: Vec<String>,
+    plain: Vec<String>,
+    top: usize,
+    rows: usize,
+    cols: usize,
+    search: Option<String>,
+    message: String,
+}
+
+impl Pager {
+    fn new(lines: Vec<String>) -> Pager {
+        let (rows, cols) = terminal_size().unwrap_or((24, 80));
+        let plain = lines.iter().map(|line| strip_ansi(line)).collect();
+        Pager { lines, plain, top: 0, rows, cols, search: None, message: String::new() }
+    }
+    
+    fn run(&mut self) -> Result<()> {
+        let _screen = RawScreen::enter()?;
+        self.event_loop()
+    }
+    
+    fn page_height(&self) -> usize {
+        self.rows.saturating_sub(1).max(1)
+    }
+    
+    fn max_top(&self) -> usize {
+        self.lines.len().saturating_sub(self.page_height())
+    }
+    
+    fn event_loop(&mut self) -> Result<()> {
+        use crossterm::event::{self, Event, KeyCode};
+        
+        loop {
+            self.render()?;
+            
+            let key = match event::read()? {
+                Event::Key(key) if key.is_press() => key,
+                Event::Resize(cols, rows) => {
+                    (self.rows, self.cols) = (rows as usize, cols as usize);
+                    self.top = self.top.min(self.max_top());
+                    continue;
+                }
+                _ => continue,
+            };
+            
+            match key.code {
+                _ if is_ctrl_c(&key) => return Ok(()),
+                KeyCode::Char('q') => return Ok(()),
+                KeyCode::Char('j') | KeyCode::Enter | KeyCode::Down => self.top = (self.top + 1).min(self.max_top()),
+                KeyCode::Char('k') | KeyCode::Up => self.top = self.top.saturating_sub(1),
+                KeyCode::Char(' ' | 'f') | KeyCode::PageDown => self.top = (self.top + self.page_height()).min(self.max_top()),
+                KeyCode::Char('b') | KeyCode::PageUp => self.top = self.top.saturating_sub(self.page_height()),
+                KeyCode::Char('g') | KeyCode::Home => self.top = 0,
+                KeyCode::Char('G') | KeyCode::End => self.top = self.max_top(),
+                KeyCode::Char('/') => {
+                    if let Some(query) = self.prompt()? {
+                        self.search = Some(query);
+                        self.jump(true, true);
+                    }
+                }
+                KeyCode::Char('n') => self.jump(true, false),
+                KeyCode::Char('N') => self.jump(false, false),
+                _ => {}
+            }
+        }
+    }
+    
+    // Moves to the next/previous line containing the search term
+    fn jump(&mut self, forward: bool, include_current: bool) {
+        let Some(query) = &self.search else {
+            return;
+        };
+        
+        let found = if forward {
+            let start = if include_current { self.top } else { self.top + 1 };
+            (start..self.plain.len()).find(|&i| self.plain[i].contains(query.as_str()))
+        } else {
+            (0..self.top).rev().find(|&i| self.plain[i].contains(query.as_str()))
+        };
+        
+        match found {
+            Some(i) => {
+                self.top = i.min(self.max_top());
+                self.message.clear();
+            }
+            None => self.message = format!("Pattern not found: {}", query),
+        }
+    }
+    
+    fn prompt(&mut self) -> Result<Option<String>> {
+        use crossterm::event::{self, Event, KeyCode};
+        use std::io::Write;
+        
+        let mut query = String::new();
+        
+        loop {
+            print!("\x1b[{};1H\x1b[2K/{}", self.rows, query);
+            std::io::stdout().flush()?;
+            
+            let Event::Key(key) = event::read()? else {
+                continue;
+            };
+            if !key.is_press() {
+                continue;
+            }
+            match key.code {
+                _ if is_ctrl_c(&key) => return Ok(None),
+                KeyCode::Enter => return Ok(Some(query).filter(|q| !q.is_empty())),
+                KeyCode::Esc => return Ok(None),
+                KeyCode::Backspace => {
+                    query.pop();
+                }
+                KeyCode::Char(c) => query.push(c),
+                _ => {}
+            }
+        }
+    }
+    
+    fn render(&self) -> Result<()> {
+        use std::io::Write;
+        
+        print!("{}", self.frame());
+        std::io::stdout().flush()?;
+        Ok(())
+    }
+    
+    fn frame(&self) -> String {
+        let mut frame = String::from("\x1b[H\x1b[2J");
+        let end = (self.top + self.page_height()).min(self.lines.len());
+        
+        for (line, plain) in self.lines[self.top..end].iter().zip(&self.plain[self.top..end]) {
+            // A matching line is drawn from its plain text so the highlight
+            // can't land inside a color code
+            let line = match &self.search {
+                Some(query) if plain.contains(query.as_str()) => truncate_to_width(plain, self.cols)
+                    .replace(query.as_str(), &format!("\x1b[7m{}\x1b[27m", query)),
+                _ => truncate_to_width(line, self.cols),
+            };
+            frame.push_str(&line);
+            frame.push_str("\x1b[0m\r\n");
+        }
+        
+        let status = if self.message.is_empty() {
+            format!("lines {}-{}/{}  (/ search, n/N next/prev, q quit)", 
+                self.top + 1, end, self.lines.len())
+        } else {
+            self.message.clone()
+        };
+        frame.push_str(&format!("\x1b[{};1H\x1b[7m{}\x1b[27m", self.rows, truncate_to_width(&status, self.cols)));
+        
+        frame
+    }
+}
+
+// Escape sequences are copied through without taking up width
+fn truncate_to_width(text: &str, width: usize) -> String {
+    let mut out = String::new();
+    let mut used = 0;
+    let mut chars = text.chars();
+    
+    while let Some(c) = chars.next() {
+        if c == '\x1b' {
+            out.push(c);
+            for c in chars.by_ref() {
+                out.push(c);
+                if c.is_ascii_alphabetic() {
+                    break;
+                }
+            }
+            continue;
+        }
+        
+        used += char_width(c);
+        if used > width {
+            break;
+        }
+        out.push(c);
+    }
+    
+    out
+}
+
+// Drops CSI sequences like the color codes `colored` emits
+fn strip_ansi(text: &str) -> String {
+    let mut out = String::new();
+    let mut chars = text.chars();
+    
+    while let Some(c) = chars.next() {
+        if c == '\x1b' {
+            // the final letter ends the sequence
+            chars.by_ref().find(|c| c.is_ascii_alphabetic());
+        } else {
+            out.push(c);
+        }
+    }
+    
+    out
+}
+
+// Raw mode, the alternate screen and a hidden cursor for as long as it lives.
+// Dropping it restores the terminal, so an error or a panic inside the pager
+// still hands the shell back a usable terminal
+struct RawScreen;
+
+impl RawScreen {
+    fn enter() -> Result<RawScreen> {
+        use crossterm::{cursor, terminal};
+        
+        terminal::enable_raw_mode()?;
+        let screen = RawScreen;
+        crossterm::execute!(std::io::stdout(), terminal::EnterAlternateScreen, cursor::Hide)?;
+        Ok(screen)
+    }
+}
+
+impl Drop for RawScreen {
+    fn drop(&mut self) {
+        use crossterm::{cursor, terminal};
+        
+        let _ = crossterm::execute!(std::io::stdout(), cursor::Show, terminal::LeaveAlternateScreen);
+        let _ = terminal::disable_raw_mode();
+    }
+}
+
+// Raw mode turns Ctrl+C into a key instead of SIGINT
+fn is_ctrl_c(key: &crossterm::event::KeyEvent) -> bool {
+    use crossterm::event::{KeyCode, KeyModifiers};
+    
+    key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL)
+}
+
+fn read_byte() -> Result<u8> {
+    use std::io::Read;
+    
+    let mut buf = [0u8; 1];
+    std::io::stdin().read_exact(&mut buf)?;
+    Ok(buf[0])
+}
+
+fn stty(args: &[&str]) -> Result<String> {
+    let output = Command::new("stty")
+        .args(args)
+        .stdin(Stdio::inherit())
+        .output()?;
+    
+    if !output.status.success() {
+        anyhow::bail!("stty failed");
+    }
+    
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+// (rows, cols)
+fn terminal_size() -> Option<(usize, usize)> {
+    let (cols, rows) = crossterm::terminal::size().ok()?;
+    Some((rows as usize, cols as usize))
+}
+
+fn pretty_json_line(line: &str) -> String {
+    let Ok(Value::Object(mut fields)) = serde_json::from_str::<Value>(line) else {
+        return line.to_string();
+    };
+    
+    let mut take = |keys: &[&str]| {
+        keys.iter()
+            .find_map(|key| fields.remove(*key))
+            .map(|v| v.as_str().map(String::from).unwrap_or_else(|| v.to_string()))
+    };
+    
+    let timestamp = take(&["timestamp", "time", "ts", "@timestamp"]).unwrap_or_default();
+    let level = take(&["level", "severity", "lvl"]).unwrap_or_default();
+    let message = take(&["message", "msg"]).unwrap_or_default();
+    
+    let level_colored = match level.to_lowercase().as_str() {
+        "error" | "err" | "fatal" | "critical" | "panic" => level.to_uppercase().danger().bold(),
+        "warn" | "warning" => level.to_uppercase().warning(),
+        "debug" | "trace" => level.to_uppercase().muted(),
+        _ => level.to_uppercase().normal(),
+    };
+    
+    let extra: Vec<String> = fields
+        .iter()
+        .map(|(k, v)| format!("{}={}", k, v.as_str().map(String::from).unwrap_or_else(|| v.to_string())))
+        .collect();
+    
+    let mut out = format!("{} {:<5} {}", timestamp.muted(), level_colored, message);
+    if !extra.is_empty() {
+        out.push_str(&format!(" {}", extra.join(" ").muted()));
+    }
+    
+    out
+}
+
+// An empty command opens an interactive /bin/sh
+fn exec_pod(
+    pod_pattern: &str,
+    namespace: Option<String>,
+    command: &[String],
+    workdir: Option<&str>,
+    env: &[String],
+    all_containers: bool,
+    retry_on_restart: bool,
+) -> Result<()> {
+    for var in env {
+        match var.split_once('=') {
+            Some((key, _)) if is_env_name(key) => {}
+            _ => anyhow::bail!("Invalid env '{}' (expected KEY=VAL)", var),
+        }
+    }
+    
+    if all_containers && command.is_empty() {
+        anyhow::bail!("--all-containers is non-interactive; give a command with -c or after --");
+    }
+    
+    // Re-running an interactive shell would silently drop the user's session state
+    if retry_on_restart && command.is_empty() {
+        anyhow::bail!("--retry-on-restart is for non-interactive commands; give a command with -c or after --");
+    }
+    let interactive = command.is_empty();
+    
+    let default_shell = ["/bin/sh".to_string()];
+    let command = if command.is_empty() { &default_shell[..] } else { command };
+    
+    let pod = resolve_pod(pod_pattern, namespace)?;
+    let (pod_name, ns) = pod_name_ns(&pod);
+    
+    println!("{} Executing in pod: {} (namespace: {})", 
+        "[INFO]".accent(), pod_name.bold(), ns.muted());
+    println!("{} Command: {}", "[INFO]".accent(), command.join(" ").warning());
+    if let Some(dir) = workdir {
+        println!("{} Workdir: {}", "[INFO]".accent(), dir.warning());
+    }
+    if interactive {
+        println!("{} {}", "[INFO]".accent(), SHELL_EXIT_HINT);
+    }
+    println!("{}", "-".repeat(100));
+    
+    // Plain exec unless we need a shell to set up the environment
+    let wrapped;
+    let argv: Vec<&str> = if workdir.is_none() && env.is_empty() {
+        command.iter().map(String::as_str).collect()
+    } else {
+        let text = match command {
+            [single] => single.clone(),
+            _ => command.iter().map(|a| shell_quote(a)).collect::<Vec<_>>().join(" "),
+        };
+        wrapped = wrap_command(&text, workdir, env);
+        vec!["sh", "-c", &wrapped]
+    };
+    
+    if all_containers {
+        return exec_all_containers(&pod, &argv);
+    }
+    
+    let owner = pod_owner(&pod);
+    let mut pod_name = pod_name;
+    let mut uid = pod["metadata"]["uid"].as_str().unwrap_or("").to_string();
+    let mut retries = 0;
+    
+    // Commands from -c, -- or a preset also run in scripts and CI: no TTY to
+    // allocate there, and kubectl would warn "Unable to use a TTY"
+    let stdin_flags = if interactive { "-it" } else { "-i" };
+    
+    loop {
+        let mut args = vec!["exec", stdin_flags, &pod_name, "-n", &ns, "--"];
+        args.extend(&argv);
+        
+        let status = kubectl(&args).status()?;
+        
+        if status.success() {
+            return Ok(());
+        }
+        
+        // A failing command and a vanished pod look the same from the exit code
+        if !pod_replaced(&pod_name, &ns, &uid) {
+            anyhow::bail!("Failed to exec into pod");
+        }
+        
+        if !retry_on_restart {
+            let successor = owner.as_ref()
+                .and_then(|owner| running_successor(&ns, owner, &uid))
+                .map(|name| format!("; reconnect with: kdbg exec {} -n {}", name, ns))
+                .unwrap_or_default();
+            let kind = if interactive { "Session ended" } else { "Command failed" };
+            anyhow::bail!("{}: pod {} was replaced{}", kind, pod_name, successor);
+        }
+        
+        if retries == EXEC_RETRY_LIMIT {
+            anyhow::bail!("Pod was replaced {} times, giving up", retries + 1);
+        }
+        retries += 1;
+        
+        let next = wait_for_replacement(&pod_name, &ns, owner.as_ref(), &uid)?;
+        let (next_name, _) = pod_name_ns(&next);
+        println!("\n{} pod {} was replaced, re-running in {} (retry {}/{})", 
+            "[INFO]".warning(), pod_name, next_name.bold(), retries, EXEC_RETRY_LIMIT);
+        println!("{}", "-".repeat(100));
+        
+        pod_name = next_name;
+        uid = next["metadata"]["uid"].as_str().unwrap_or("").to_string();
+    }
+}
+
+// Presets are command lines, so they run through sh -c and may use pipes,
+// redirects and && like they would in a terminal
+fn exec_preset(name: &str) -> Result<Vec<String>> {
+    let config = config();
+    
+    let Some(command) = config.get(&format!("exec_presets.{}", name)) else {
+        let defined = config.section_keys("exec_presets");
+        if defined.is_empty() {
+            anyhow::bail!("No exec preset '{}'; define it under [exec_presets] in the config file", name);
+        }
+        anyhow::bail!("No exec preset '{}'; defined presets: {}", name, defined.join(", "));
+    };
+    
+    Ok(vec!["sh".to_string(), "-c".to_string(), command])
+}
+
+// True once the pod is deleted, terminating, or recreated under the same name
+fn pod_replaced(pod_name: &str, ns: &str, uid: &str) -> bool {
+    let Ok(output) = run_kubectl(&["get", "pod", pod_name, "-n", ns, "-o", "json"]) else {
+        return false;
+    };
+    
+    if !output.status.success() {
+        return String::from_utf8_lossy(&output.stderr).contains("NotFound");
+    }
+    
+    let Ok(pod) = serde_json::from_slice::<Value>(&output.stdout) else {
+        return false;
+    };
+    pod["metadata"]["uid"].as_str() != Some(uid) || !pod["metadata"]["deletionTimestamp"].is_null()
+}
+
+// A running pod from the same controller, without waiting for one to appear
+fn running_successor(ns: &str, owner: &(String, String), uid: &str) -> Option<String> {
+    let pods = fetch_pods(&Some(ns.to_string()), None).ok()?;
+    
+    pods.iter()
+        .filter(|pod| pod_owner(pod).as_ref() == Some(owner))
+        .filter(|pod| pod["metadata"]["uid"].as_str() != Some(uid))
+        .filter(|pod| pod["metadata"]["deletionTimestamp"].is_null())
+        .filter(|pod| pod["status"]["phase"].as_str() == Some("Running"))
+        .max_by_key(|pod| pod["metadata"]["creationTimestamp"].as_str().unwrap_or("").to_string())
+        .and_then(|pod| pod["metadata"]["name"].as_str().map(str::to_string))
+}
+
+fn exec_all_containers(pod: &Value, argv: &[&str]) -> Result<()> {
+    let (pod_name, ns) = pod_name_ns(pod);
+    let containers = pod_containers(pod);
+    let mut failed = Vec::new();
+    
+    for container in &containers {
+        println!("\n{} {}", "==>".accent().bold(), container.bold());
+        
+        let mut args = vec!["exec", &pod_name, "-n", &ns, "-c", container, "--"];
+        args.extend(argv);
+        
+        let status = kubectl(&args).stdin(Stdio::null()).status()?;
+        let code = status.code().map_or("signal".to_string(), |c| c.to_string());
+        
+        if status.success() {
+            println!("{} exit code {}", "[OK]".success(), code);
+        } else {
+            println!("{} exit code {}", "[FAILED]".danger(), code);
+            failed.push(container.as_str());
+        }
+    }
+    
+    println!("{}", "-".repeat(100));
+    println!("{} {}/{} containers succeeded", "[INFO]".accent(), 
+        containers.len() - failed.len(), containers.len());
+    
+    if !failed.is_empty() {
+        anyhow::bail!("Command failed in: {}", failed.join(", "));
+    }
+    
+    Ok(())
+}
+
+fn wrap_command(command: &str, workdir: Option<&str>, env: &[String]) -> String {
+    let mut parts = Vec::new();
+    
+    if let Some(dir) = workdir {
+        parts.push(format!("cd {}", shell_quote(dir)));
+    }
+    
+    // Keys are validated identifiers; only the values need quoting
+    for var in env {
+        if let Some((key, value)) = var.split_once('=') {
+            parts.push(format!("export {}={}", key, shell_quote(value)));
+        }
+    }
+    
+    parts.push(command.to_string());
+    parts.join(" && ")
+}
+
+fn shell_quote(value: &str) -> String {
+    let is_safe = !value.is_empty() && value
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || "-_./:=@%+,".contains(c));
+    
+    if is_safe {
+        value.to_string()
+    } else {
+        format!("'{}'", value.replace('\'', "'\\''"))
+    }
+}
+
+fn is_env_name(key: &str) -> bool {
+    let mut chars = key.chars();
+    matches!(chars.next(), Some(c) if c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+// Kubernetes object names are DNS subdomains, which also keeps them safe to
+// interpolate into field selectors
+fn validate_resource_name(name: &str) -> Result<()> {
+    let is_valid = !name.is_empty()
+        && name.len() <= 253
+        && name.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' || c == '.')
+        && name.starts_with(|c: char| c.is_ascii_alphanumeric())
+        && name.ends_with(|c: char| c.is_ascii_alphanumeric());
+    
+    if !is_valid {
+        anyhow::bail!("Invalid resource name '{}'", name);
+    }
+    
+    Ok(())
+}
+
+fn describe_pod(pod_pattern: &str, namespace: Option<String>) -> Result<()> {
+    let (pod_name, ns) = find_pod(pod_pattern, namespace)?;
+    
+    println!("{} Describing pod: {} (namespace: {})", 
+        "[INFO]".accent(), pod_name.bold(), ns.muted());
+    println!("{}", "-".repeat(100));
+    
+    let mut args = vec!["describe", "pod", &pod_name, "-n", &ns];
+    args.extend(extra_kubectl_args());
+    let status = kubectl(&args).status()?;
+    
+    if !status.success() {
+        anyhow::bail!("Failed to describe pod");
+    }
+    
+    Ok(())
+}
+
+// Babysits one pod: its phase, readiness, per-container state and latest
+// events, refreshed until the pod turns Ready
+fn watch_describe(pod_pattern: &str, namespace: Option<String>, interval: u64) -> Result<()> {
+    use std::fmt::Write;
+    
+    let (pod_name, ns) = find_pod(pod_pattern, namespace)?;
+    let title = format!("kdbg describe {} -n {}", pod_name, ns);
+    let selector = format!("involvedObject.name={}", pod_name);
+    
+    let last = watch_screen(&title, interval, || {
+        let output = match run_kubectl(&["get", "pod", &pod_name, "-n", &ns, "-o", "json"]) {
+            Ok(output) if output.status.success() => output,
+            Ok(output) => return (format!("{} {}\n", "[ERROR]".danger(), String::from_utf8_lossy(&output.stderr).trim()), false),
+            Err(e) => return (format!("{} {}\n", "[ERROR]".danger(), e), false),
+        };
+        let Ok(pod) = serde_json::from_slice::<Value>(&output.stdout) else {
+            return (format!("{} Unreadable pod JSON\n", "[ERROR]".danger()), false);
+        };
+        
+        let ready = is_pod_ready(&pod);
+        let phase = pod["status"]["phase"].as_str().unwrap_or("Unknown");
+        
+        let mut out = String::new();
+        writeln!(out, "{} {} (namespace: {})", "Pod:".accent().bold(), pod_name.bold(), ns.muted()).unwrap();
+        writeln!(out, "{}", "-".repeat(100)).unwrap();
+        writeln!(out, "{:<16} {}", "Status", phase_colored(phase, phase)).unwrap();
+        writeln!(out, "{:<16} {}", "Ready", if ready { "yes".success() } else { "no".danger() }).unwrap();
+        let restarts = restart_count(&pod);
+        writeln!(out, "{:<16} {}", "Restarts", RestartThresholds::load(None).colored(restarts, &restarts.to_string())).unwrap();
+        for status in pod["status"]["containerStatuses"].as_array().into_iter().flatten() {
+            writeln!(out, "{}", container_status_line(status)).unwrap();
+        }
+        for line in condition_lines(&pod) {
+            writeln!(out, "{}", line).unwrap();
+        }
+        
+        if let Ok(mut events) = fetch_events(&["-n", &ns, "--field-selector", &selector]) {
+            events.sort_by_key(|e| event_timestamp(e, "lastTimestamp"));
+            writeln!(out, "\n{}", "Recent events:".accent().bold()).unwrap();
+            for event in events.iter().rev().take(5).rev() {
+                let kind = event["type"].as_str().unwrap_or("");
+                let reason = pad(event["reason"].as_str().unwrap_or(""), 20);
+                let reason = if kind == "Warning" { reason.warning() } else { reason.normal() };
+                writeln!(out, "{} {}", reason, event["message"].as_str().unwrap_or("").trim()).unwrap();
+            }
+        }
+        
+        (out, ready)
+    })?;
+    
+    if let Some(summary) = last {
+        print!("{}", summary);
+        println!("\n{} {} is Ready", "[SUCCESS]".success(), pod_name);
+    }
+    
+    Ok(())
+}
+
+fn describe_selector(selector: &str, namespace: Option<String>, page: bool, yes: bool) -> Result<()> {
+    use std::io::IsTerminal;
+    
+    let targets = resolve_selector(selector, &namespace)?;
+    
+    if targets.len() > DESCRIBE_CONFIRM_LIMIT
+        && !yes
+        && !confirm(&format!("Describe {} pods matching '{}'?", targets.len(), selector))?
+    {
+        anyhow::bail!("Aborted");
+    }
+    
+    let page = page && std::io::stdout().is_terminal() && std::io::stdin().is_terminal();
+    let mut paged = Vec::new();
+    let mut results = OpResults::default();
+    
+    for target in &targets {
+        let PodRef { name: pod_name, namespace: ns } = target;
+        let header = format!("Pod: {} (namespace: {})", pod_name, ns);
+        
+        if page {
+            let args: Vec<&str> = ["describe", "pod", pod_name, "-n", ns].into_iter().chain(extra_kubectl_args()).collect();
+            let output = kubectl(&args).stderr(Stdio::inherit()).output();
+            let Ok(output) = results.record(target, output.map(|o| (o.status, o))) else {
+                continue;
+            };
+            paged.push("=".repeat(100));
+            paged.push(header);
+            paged.push("=".repeat(100));
+            paged.extend(String::from_utf8_lossy(&output.stdout).lines().map(String::from));
+            paged.push(String::new());
+            continue;
+        }
+        
+        println!("{}", "=".repeat(100).muted());
+        println!("{} {}", "[INFO]".accent(), header.bold());
+        println!("{}", "=".repeat(100).muted());
+        
+        let args: Vec<&str> = ["describe", "pod", pod_name, "-n", ns].into_iter().chain(extra_kubectl_args()).collect();
+        if results.record(target, kubectl(&args).status().map(|s| (s, ()))).is_err() {
+            eprintln!("{} Failed to describe {}", "[ERROR]".danger(), pod_name);
+        }
+        println!();
+    }
+    
+    if page {
+        Pager::new(paged).run()?;
+    }
+    
+    results.finish("describe")
+}
+
+// One pod's outcome in a --selector operation
+struct OpResult {
+    pod: String,
+    namespace: String,
+    ok: bool,
+    // "exit N", "dry run", or the error that kept kubectl from running
+    detail: String,
+}
+
+// Collects per-pod outcomes while a --selector command works through its pods,
+// so the end of the output says which ones failed instead of leaving it to
+// scrolling back through interleaved kubectl output
+#[derive(Default)]
+struct OpResults(Vec<OpResult>);
+
+impl OpResults {
+    // Records the outcome and hands back whatever came with a successful run
+    fn record<T>(&mut self, pod: &PodRef, outcome: std::io::Result<(std::process::ExitStatus, T)>) -> std::result::Result<T, ()> {
+        let (ok, detail, value) = match outcome {
+            Ok((status, value)) => {
+                let detail = match status.code() {
+                    Some(code) => format!("exit {}", code),
+                    None => "killed by a signal".to_string(),
+                };
+                (status.success(), detail, status.success().then_some(value))
+            }
+            Err(e) => (false, e.to_string(), None),
+        };
+        
+        self.0.push(OpResult { pod: pod.name.clone(), namespace: pod.namespace.clone(), ok, detail });
+        value.ok_or(())
+    }
+    
+    fn record_dry_run(&mut self, pod: &PodRef) {
+        self.0.push(OpResult { pod: pod.name.clone(), namespace: pod.namespace.clone(), ok: true, detail: "dry run".to_string() });
+    }
+    
+    // Prints the summary table; fails when any pod did
+    fn finish(self, action: &str) -> Result<()> {
+        let failed = self.0.iter().filter(|r| !r.ok).count();
+        
+        println!("{}", "-".repeat(100));
+        println!("{:<40} {:<15} {:<8} DETAIL", "POD", "NAMESPACE", "RESULT");
+        println!("{}", "-".repeat(100));
+        for r in &self.0 {
+            let result = if r.ok { pad("ok", 8).success() } else { pad("failed", 8).danger() };
+            println!("{} {} {} {}",
+                pad(&r.pod, 40).accent(), namespace_colored(&r.namespace, &pad(&r.namespace, 15)), result, r.detail.muted());
+        }
+        println!("{}", "-".repeat(100));
+        
+        if failed > 0 {
+            anyhow::bail!("Failed to {} {} of {} pods", action, failed, self.0.len());
+        }
+        println!("{} All {} pods: {} ok", "[SUCCESS]".success(), self.0.len(), action);
+        
+        Ok(())
+    }
+}
+
+// Anything but y/yes (including a non-interactive stdin) counts as no
+fn confirm(prompt: &str) -> Result<bool> {
+    use std::io::{IsTerminal, Write};
+    
+    if !std::io::stdin().is_terminal() {
+        eprintln!("{} {} Pass --yes to confirm non-interactively", "[INFO]".warning(), prompt);
+        return Ok(false);
+    }
+    
+    print!("{} {} [y/N] ", "[?]".warning(), prompt);
+    std::io::stdout().flush()?;
+    
+    let mut answer = String::new();
+    std::io::stdin().read_line(&mut answer)?;
+    
+    Ok(matches!(answer.trim().to_lowercase().as_str(), "y" | "yes"))
+}
+
+// The pod lifecycle order kubectl describe uses, anything else after
+const CONDITION_ORDER: [&str; 4] = ["PodScheduled", "Initialized", "ContainersReady", "Ready"];
+
+fn sorted_conditions(pod: &Value) -> Vec<&Value> {
+    let mut conditions: Vec<&Value> = pod["status"]["conditions"].as_array().into_iter().flatten().collect();
+    conditions.sort_by_key(|c| {
+        CONDITION_ORDER.iter().position(|kind| c["type"] == *kind).unwrap_or(CONDITION_ORDER.len())
+    });
+    conditions
+}
+
+// "Conditions" rows for info and describe --watch. A False condition carries
+// its reason and message, which usually says why the pod isn't Ready
+// (e.g. "Unschedulable: 0/3 nodes are available: insufficient cpu")
+fn condition_lines(pod: &Value) -> Vec<String> {
+    sorted_conditions(pod)
+        .iter()
+        .enumerate()
+        .map(|(i, c)| {
+            let label = if i == 0 { "Conditions" } else { "" };
+            let kind = c["type"].as_str().unwrap_or("Unknown");
+            let status = c["status"].as_str().unwrap_or("Unknown");
+            // True conditions sometimes keep a stale reason; only the others explain anything
+            let reason = if status == "True" { Vec::new() } else { vec![c["reason"].as_str(), c["message"].as_str()] };
+            let reason = reason
+                .into_iter()
+                .flatten()
+                .filter(|s| !s.is_empty())
+                .collect::<Vec<_>>()
+                .join(": ");
+            let (status_text, detail) = if reason.is_empty() {
+                (status.to_string(), String::new())
+            } else {
+                (pad(status, 6), format!(" {}", reason.danger()))
+            };
+            let status_colored = match status {
+                "True" => status_text.success(),
+                "False" => status_text.danger(),
+                _ => status_text.warning(),
+            };
+            format!("{:<16} {} {}{}", label, pad(kind, 16), status_colored, detail)
+        })
+        .collect()
+}
+
+// A describe-lite built from the pod JSON find_pod already fetched
+fn show_info(pod_pattern: &str, namespace: Option<String>, format: InfoFormat, probes: Option<&[String]>) -> Result<()> {
+    let targets = probes.map(parse_probes).transpose()?;
+    
+    let pod = resolve_pod(pod_pattern, namespace)?;
+    let (pod_name, ns) = pod_name_ns(&pod);
+    
+    let images: Vec<Value> = pod["spec"]["containers"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .map(|c| serde_json::json!({ "container": c["name"], "image": c["image"] }))
+        .collect();
+    let created = pod["metadata"]["creationTimestamp"].as_str();
+    
+    let info = serde_json::json!({
+        "name": pod_name,
+        "namespace": ns,
+        "status": pod["status"]["phase"],
+        "images": images,
+        "node": pod["spec"]["nodeName"],
+        "ip": pod["status"]["podIP"],
+        "serviceAccount": pod["spec"]["serviceAccountName"],
+        "qosClass": pod["status"]["qosClass"],
+        "restarts": restart_count(&pod),
+        "created": created,
+        "age": created.map(calculate_age),
+        "labels": pod["metadata"]["labels"],
+        "conditions": sorted_conditions(&pod)
+            .iter()
+            .map(|c| serde_json::json!({
+                "type": c["type"], "status": c["status"], "reason": c["reason"], "message": c["message"],
+            }))
+            .collect::<Vec<_>>(),
+    });
+    
+    let checks = targets.map(|targets| network_checks(&pod_name, &ns, &targets));
+    let scheduling = pod["spec"]["nodeName"].is_null().then(|| scheduling_info(&pod, &pod_name, &ns));
+    
+    if let InfoFormat::Json = format {
+        let mut info = info;
+        if let Some(checks) = &checks {
+            info["network"] = serde_json::to_value(checks.iter().map(NetCheck::to_json).collect::<Vec<_>>())?;
+        }
+        if let Some(scheduling) = scheduling {
+            info["scheduling"] = scheduling;
+        }
+        println!("{}", serde_json::to_string_pretty(&info)?);
+        return Ok(());
+    }
+    
+    let field = |v: &Value| v.as_str().map(String::from).unwrap_or_else(|| "<none>".to_string());
+    
+    println!("{} {}", "Pod:".accent().bold(), pod_name.bold());
+    println!("{}", "-".repeat(100));
+    println!("{:<16} {}", "Namespace", ns);
+    println!("{:<16} {}", "Status", field(&info["status"]));
+    for image in &images {
+        println!("{:<16} {} ({})", "Image", field(&image["image"]), field(&image["container"]).muted());
+    }
+    println!("{:<16} {}", "Node", field(&info["node"]));
+    println!("{:<16} {}", "IP", field(&info["ip"]));
+    println!("{:<16} {}", "Service account", field(&info["serviceAccount"]));
+    println!("{:<16} {}", "QoS", field(&info["qosClass"]));
+    let restarts = info["restarts"].as_u64().unwrap_or(0);
+    println!("{:<16} {}", "Restarts", RestartThresholds::load(None).colored(restarts, &restarts.to_string()));
+    println!("{:<16} {}", "Age", field(&info["age"]));
+    
+    match pod["metadata"]["labels"].as_object() {
+        Some(labels) if !labels.is_empty() => {
+            for (i, (key, value)) in labels.iter().enumerate() {
+                let label = if i == 0 { "Labels" } else { "" };
+                println!("{:<16} {}={}", label, key, value.as_str().unwrap_or(""));
+            }
+        }
+        _ => println!("{:<16} <none>", "Labels"),
+    }
+    
+    for line in condition_lines(&pod) {
+        println!("{}", line);
+    }
+    
+    if let Some(scheduling) = &scheduling {
+        print_scheduling(scheduling);
+    }
+    
+    if let Some(checks) = &checks {
+        print_network_checks(checks, &pod_name, &ns);
+    }
+    
+    Ok(())
+}
+
+// What the scheduler has to satisfy for a pod that has no node yet, next to
+// its latest FailedScheduling message, which names the constraint that failed
+fn scheduling_info(pod: &Value, pod_name: &str, ns: &str) -> Value {
+    let spec = &pod["spec"];
+    
+    let node_selector: Vec<String> = spec["nodeSelector"].as_object().into_iter().flatten()
+        .map(|(key, value)| format!("{}={}", key, value.as_str().unwrap_or("")))
+        .collect();
+    
+    let tolerations: Vec<String> = spec["tolerations"].as_array().into_iter().flatten()
+        .map(|t| {
+            let key = t["key"].as_str().unwrap_or("*");
+            let target = match (t["operator"].as_str(), t["value"].as_str()) {
+                (Some("Exists"), _) => format!("{} exists", key),
+                (_, Some(value)) => format!("{}={}", key, value),
+                _ => key.to_string(),
+            };
+            format!("{}:{}", target, t["effect"].as_str().unwrap_or("any effect"))
+        })
+        .collect();
+    
+    let requests: Vec<String> = spec["containers"].as_array().into_iter().flatten()
+        .map(|c| {
+            let requests: Vec<String> = c["resources"]["requests"].as_object().into_iter().flatten()
+                .map(|(resource, amount)| format!("{}={}", resource, amount.as_str().unwrap_or("")))
+                .collect();
+            let requests = if requests.is_empty() { "none".to_string() } else { requests.join(" ") };
+            format!("{}: {}", c["name"].as_str().unwrap_or(""), requests)
+        })
+        .collect();
+    
+    let selector = format!("involvedObject.name={},reason=FailedScheduling", pod_name);
+    let event = fetch_events(&["-n", ns, "--field-selector", &selector])
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|e| e["reason"] == "FailedScheduling")
+        .max_by_key(|e| event_timestamp(e, "lastTimestamp"));
+    
+    serde_json::json!({
+        "nodeSelector": node_selector,
+        "affinity": affinity_rules(&spec["affinity"]),
+        "tolerations": tolerations,
+        "requests": requests,
+        "failedScheduling": event.map(|e| serde_json::json!({
+            "message": e["message"],
+            "count": event_count(&e),
+            "lastSeen": event_rfc3339(&e),
+        })),
+    })
+}
+
+// One line per rule, e.g. "node: topology.kubernetes.io/zone In [eu-1a, eu-1b] (required)"
+fn affinity_rules(affinity: &Value) -> Vec<String> {
+    let expressions = |term: &Value| -> String {
+        term["matchExpressions"].as_array().into_iter().flatten()
+            .map(|e| {
+                let values: Vec<&str> = e["values"].as_array().into_iter().flatten().filter_map(Value::as_str).collect();
+                match values.as_slice() {
+                    [] => format!("{} {}", e["key"].as_str().unwrap_or(""), e["operator"].as_str().unwrap_or("")),
+                    _ => format!("{} {} [{}]", e["key"].as_str().unwrap_or(""), e["operator"].as_str().unwrap_or(""), values.join(", ")),
+                }
+            })
+            .chain(term["matchLabels"].as_object().into_iter().flatten()
+                .map(|(key, value)| format!("{}={}", key, value.as_str().unwrap_or(""))))
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+    let mut rules = Vec::new();
+    
+    let node = &affinity["nodeAffinity"];
+    let required = node["requiredDuringSchedulingIgnoredDuringExecution"]["nodeSelectorTerms"].as_array().into_iter().flatten();
+    for term in required {
+        rules.push(format!("node: {} (required)", expressions(term)));
+    }
+    for preferred in node["preferredDuringSchedulingIgnoredDuringExecution"].as_array().into_iter().flatten() {
+        rules.push(format!("node: {} (preferred, weight {})", expressions(&preferred["preference"]), preferred["weight"]));
+    }
+    
+    for (kind, label) in [("podAffinity", "pods"), ("podAntiAffinity", "away from pods")] {
+        for term in affinity[kind]["requiredDuringSchedulingIgnoredDuringExecution"].as_array().into_iter().flatten() {
+            rules.push(format!("{}: {} per {} (required)", 
+                label, expressions(&term["labelSelector"]), term["topologyKey"].as_str().unwrap_or("")));
+        }
+        for preferred in affinity[kind]["preferredDuringSchedulingIgnoredDuringExecution"].as_array().into_iter().flatten() {
+            let term = &preferred["podAffinityTerm"];
+            rules.push(format!("{}: {} per {} (preferred, weight {})", 
+                label, expressions(&term["labelSelector"]), term["topologyKey"].as_str().unwrap_or(""), preferred["weight"]));
+        }
+    }
+    
+    rules
+}
+
+fn print_scheduling(scheduling: &Value) {
+    println!("\n{}", "Scheduling (no node yet):".warning().bold());
+    
+    let rows = [
+        ("Node selector", &scheduling["nodeSelector"]),
+        ("Affinity", &scheduling["affinity"]),
+        ("Tolerations", &scheduling["tolerations"]),
+        ("Requests", &scheduling["requests"]),
+    ];
+    for (label, values) in rows {
+        let values: Vec<&str> = values.as_array().into_iter().flatten().filter_map(Value::as_str).collect();
+        if values.is_empty() {
+            println!("{:<16} <none>", label);
+        }
+        for (i, value) in values.iter().enumerate() {
+            println!("{:<16} {}", if i == 0 { label } else { "" }, value);
+        }
+    }
+    
+    match scheduling["failedScheduling"].as_object() {
+        Some(event) => println!("{:<16} {} {}", "Scheduler", 
+            event["message"].as_str().unwrap_or("").danger(),
+            format!("(x{}, {} ago)", event["count"], calculate_age(event["lastSeen"].as_str().unwrap_or(""))).muted()),
+        None => println!("{:<16} no FailedScheduling event (yet)", "Scheduler"),
+    }
+}
+
+// Cluster DNS is always checked; each --probe adds a lookup and, with a port, a connect
+fn parse_probes(probes: &[String]) -> Result<Vec<(String, Option<u16>)>> {
+    let mut targets = vec![("kubernetes.default".to_string(), None)];
+    
+    for probe in probes {
+        let target = match probe.rsplit_once(':') {
+            Some((host, port)) => {
+                let port = port.parse::<u16>()
+                    .map_err(|_| anyhow::anyhow!("Invalid port in --probe '{}' (expected HOST:PORT)", probe))?;
+                (host.to_string(), Some(port))
+            }
+            None => (probe.clone(), None),
+        };
+        if target.0.is_empty() || !target.0.chars().all(|c| c.is_ascii_alphanumeric() || "-._".contains(c)) {
+            anyhow::bail!("Invalid host in --probe '{}'", probe);
+        }
+        targets.push(target);
+    }
+    
+    Ok(targets)
+}
+
+enum NetOutcome {
+    Ok(String),
+    Failed(String),
+    // None of the fallback tools exist in the image
+    NoTool(&'static str),
+}
+
+struct NetCheck {
+    kind: &'static str,
+    target: String,
+    outcome: NetOutcome,
+}
+
+impl NetCheck {
+    fn to_json(&self) -> Value {
+        let (result, detail) = match &self.outcome {
+            NetOutcome::Ok(detail) => ("ok", detail.as_str()),
+            NetOutcome::Failed(detail) => ("failed", detail.as_str()),
+            NetOutcome::NoTool(tools) => ("no-tool", *tools),
+        };
+        serde_json::json!({ "check": self.kind, "target": self.target, "result": result, "detail": detail })
+    }
+}
+
+// Each tool runs through `sh -c` so a missing binary shows up as exit 127
+// instead of a container runtime error; the first tool that exists decides
+fn network_checks(pod_name: &str, ns: &str, targets: &[(String, Option<u16>)]) -> Vec<NetCheck> {
+    let mut checks = Vec::new();
+    
+    for (host, port) in targets {
+        let outcome = probe_in_pod(pod_name, ns, "nslookup or getent", &[
+            ("nslookup", format!("nslookup {}", host)),
+            ("getent", format!("getent hosts {}", host)),
+        ]);
+        checks.push(NetCheck { kind: "dns", target: host.clone(), outcome });
+        
+        if let Some(port) = port {
+            let outcome = probe_in_pod(pod_name, ns, "nc or wget", &[
+                ("nc", format!("nc -z -w 3 {} {}", host, port)),
+                // exit 8: the server answered with an HTTP error, so it is reachable
+                ("wget", format!("wget -q -T 3 -O /dev/null http://{}:{}/; rc=$?; [ $rc -eq 8 ] && exit 0; exit $rc", host, port)),
+            ]);
+            checks.push(NetCheck { kind: "connect", target: format!("{}:{}", host, port), outcome });
+        }
+    }
+    
+    checks
+}
+
+fn probe_in_pod(pod_name: &str, ns: &str, tools: &'static str, attempts: &[(&str, String)]) -> NetOutcome {
+    for (tool, command) in attempts {
+        let script = format!("command -v {} >/dev/null 2>&1 || exit 127; {}", tool, command);
+        let output = match run_kubectl(&["exec", pod_name, "-n", ns, "--", "sh", "-c", &script]) {
+            Ok(output) => output,
+            Err(e) => return NetOutcome::Failed(e.to_string()),
+        };
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        
+        // No shell at all (distroless): nothing else will work either
+        if stderr.contains("executable file not found") {
+            return NetOutcome::NoTool("sh");
+        }
+        
+        match output.status.code() {
+            Some(0) => return NetOutcome::Ok(format!("via {}", tool)),
+            Some(127) => continue,
+            code => {
+                let detail = String::from_utf8_lossy(&output.stdout)
+                    .lines()
+                    .chain(stderr.lines())
+                    .map(str::trim)
+                    .rfind(|line| !line.is_empty() && !line.starts_with("command terminated"))
+                    .map(str::to_string)
+                    .unwrap_or_else(|| format!("exit {}", code.unwrap_or(-1)));
+                return NetOutcome::Failed(format!("{} ({})", detail, tool));
+            }
+        }
+    }
+    
+    NetOutcome::NoTool(tools)
+}
+
+fn print_network_checks(checks: &[NetCheck], pod_name: &str, ns: &str) {
+    println!("\n{}", "Network:".accent().bold());
+    println!("{}", "-".repeat(100));
+    
+    for check in checks {
+        let (label, result) = match (check.kind, &check.outcome) {
+            ("dns", NetOutcome::Ok(detail)) => ("DNS", format!("resolves, {}", detail).success()),
+            (_, NetOutcome::Ok(detail)) => ("Connect", format!("reachable, {}", detail).success()),
+            ("dns", NetOutcome::Failed(detail)) => ("DNS", format!("does not resolve: {}", detail).danger()),
+            (_, NetOutcome::Failed(detail)) => ("Connect", format!("unreachable: {}", detail).danger()),
+            ("dns", NetOutcome::NoTool(tools)) => ("DNS", format!("not checked, no {} in the image", tools).warning()),
+            (_, NetOutcome::NoTool(tools)) => ("Connect", format!("not checked, no {} in the image", tools).warning()),
+        };
+        println!("{:<16} {} {}", label, pad(&check.target, 40), result);
+    }
+    
+    if checks.iter().any(|c| matches!(c.outcome, NetOutcome::NoTool(_))) {
+        println!("\n{} The image lacks network tools; attach a toolbox to the same network namespace:", "[INFO]".accent());
+        println!("    kdbg debug --ephemeral {} -n {} --image nicolaka/netshoot", pod_name, ns);
+    }
+}
+
+// Only the extracted value goes to stdout so the output can be piped
+fn get_field(pod_pattern: &str, namespace: Option<String>, jsonpath: &str) -> Result<()> {
+    let (pod_name, ns) = find_pod(pod_pattern, namespace)?;
+    
+    eprintln!("{} Pod: {} (namespace: {})", "[INFO]".accent(), pod_name.bold(), ns.muted());
+    
+    let output_arg = format!("jsonpath={}", jsonpath);
+    let output = run_kubectl(&["get", "pod", &pod_name, "-n", &ns, "-o", &output_arg])?;
+    
+    if !output.status.success() {
+        anyhow::bail!("Failed to evaluate {} on pod {}: {}", 
+            jsonpath, pod_name, String::from_utf8_lossy(&output.stderr).trim());
+    }
+    
+    // kubectl leaves off the trailing newline
+    println!("{}", String::from_utf8_lossy(&output.stdout).trim_end_matches('\n'));
+    
+    Ok(())
+}
+
+fn diff_pods(pod_a: &str, pod_b: &str, namespace: Option<String>) -> Result<()> {
+    let a = resolve_pod(pod_a, namespace.clone())?;
+    let b = resolve_pod(pod_b, namespace)?;
+    
+    let (name_a, ns_a) = pod_name_ns(&a);
+    let (name_b, ns_b) = pod_name_ns(&b);
+    
+    println!("{} Diffing pod {} (namespace: {}) against {} (namespace: {})", 
+        "[INFO]".accent(), name_a.bold(), ns_a.muted(), name_b.bold(), ns_b.muted());
+    println!("{}", "-".repeat(100));
+    
+    let text_a = normalize_pod(a);
+    let text_b = normalize_pod(b);
+    
+    let diff = unified_diff(&text_a, &text_b, &name_a, &name_b);
+    if diff.is_empty() {
+        println!("{} No spec differences", "[SUCCESS]".success());
+    }
+    for line in diff {
+        println!("{}", colorize_diff_line(&line));
+    }
+    
+    Ok(())
+}
+
+fn colorize_diff_line(line: &str) -> ColoredString {
+    if line.starts_with("+++") || line.starts_with("---") {
+        line.bold()
+    } else if line.starts_with("@@") {
+        line.accent()
+    } else if line.starts_with('+') {
+        line.success()
+    } else if line.starts_with('-') {
+        line.danger()
+    } else {
+        line.normal()
+    }
+}
+
+// Strips per-instance fields so only meaningful spec differences remain
+fn normalize_pod(mut pod: Value) -> String {
+    if let Some(obj) = pod.as_object_mut() {
+        obj.remove("status");
+    }
+    
+    if let Some(meta) = pod["metadata"].as_object_mut() {
+        for key in ["name", "uid", "resourceVersion", "creationTimestamp", "managedFields", "generateName"] {
+            meta.remove(key);
+        }
+    }
+    
+    let text = serde_json::to_string_pretty(&pod).unwrap_or_default();
+    
+    // Projected service account volumes get a random suffix per pod
+    text.lines()
+        .map(|line| match line.find("kube-api-access-") {
+            Some(i) => {
+                let start = i + "kube-api-access-".len();
+                let end = line[start..]
+                    .find(|c: char| !c.is_ascii_alphanumeric())
+                    .map_or(line.len(), |n| start + n);
+                format!("{}*{}", &line[..start], &line[end..])
+            }
+            None => line.to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+// Line-based unified diff with 3 lines of context, colored for the terminal
+fn unified_diff(a: &str, b: &str, label_a: &str, label_b: &str) -> Vec<String> {
+    use similar::{ChangeTag, TextDiff};
+    
+    let diff = TextDiff::from_lines(a, b);
+    let mut unified = diff.unified_diff();
+    unified.context_radius(3);
+    
+    let mut out = Vec::new();
+    for hunk in unified.iter_hunks() {
+        if out.is_empty() {
+            out.push(format!("--- {}", label_a).danger().bold().to_string());
+            out.push(format!("+++ {}", label_b).success().bold().to_string());
+        }
+        out.push(hunk.header().to_string().accent().to_string());
+        
+        for change in hunk.iter_changes() {
+            let line = change.value().trim_end_matches(['\n', '\r']);
+            out.push(match change.tag() {
+                ChangeTag::Equal => format!(" {}", line),
+                ChangeTag::Delete => format!("-{}", line).danger().to_string(),
+                ChangeTag::Insert => format!("+{}", line).success().to_string(),
+            });
+        }
+    }
+    
+    out
+}
+
+fn show_top(namespace: Option<String>, selector: Option<&str>, aggregate: bool, total_only: bool) -> Result<()> {
+    let metrics = fetch_top(&namespace, selector)?;
+    
+    if selector.is_some() && metrics.is_empty() {
+        anyhow::bail!("No pod metrics found for selector {}", selector.unwrap_or(""));
+    }
+    
+    println!("{}", "Pod Resource Usage:".accent().bold());
+    println!("{}", "-".repeat(100));
+    println!("{:<40} {:<15} {:<12} {:<12}", "NAME", "NAMESPACE", "CPU", "MEMORY");
+    println!("{}", "-".repeat(100));
+    
+    if !total_only {
+        for m in &metrics {
+            println!("{} {} {:<12} {:<12}", 
+                pad(&m.pod, 40).accent(), namespace_colored(&m.namespace, &pad(&m.namespace, 15)), 
+                format_cpu(m.cpu_millicores), format_memory(m.memory_bytes));
+        }
+    }
+    
+    if aggregate {
+        let cpu: u64 = metrics.iter().map(|m| m.cpu_millicores).sum();
+        let memory: u64 = metrics.iter().map(|m| m.memory_bytes).sum();
+        let scope = namespace.as_deref().unwrap_or("(all)");
+        
+        if !total_only {
+            println!("{}", "-".repeat(100));
+        }
+        print_top_total(metrics.len(), scope, &format_cpu(cpu), &format_memory(memory), 12);
+    }
+    
+    println!("\nTotal: {} pods", metrics.len());
+    
+    Ok(())
+}
+
+// Samples kept per pod for the --watch sparklines
+const SPARKLINE_SAMPLES: usize = 20;
+
+// Live table with a short usage history per pod, so a climbing line (a leak,
+// a CPU spike) shows up without comparing numbers between refreshes
+fn watch_top(namespace: Option<String>, selector: Option<&str>, interval: u64) -> Result<()> {
+    use std::collections::VecDeque;
+    
+    let sparklines = sparklines_supported();
+    let mut history: BTreeMap<(String, String), VecDeque<(u64, u64)>> = BTreeMap::new();
+    
+    watch_screen("kdbg top", interval, || {
+        use std::fmt::Write;
+        
+        let metrics = match fetch_top(&namespace, selector) {
+            Ok(metrics) => metrics,
+            Err(e) => return (format!("{} {}\n", "[ERROR]".danger(), e), false),
+        };
+        
+        // Pods that went away take their history with them
+        history.retain(|key, _| metrics.iter().any(|m| (&m.namespace, &m.pod) == (&key.0, &key.1)));
+        for m in &metrics {
+            let samples = history.entry((m.namespace.clone(), m.pod.clone())).or_default();
+            if samples.len() == SPARKLINE_SAMPLES {
+                samples.pop_front();
+            }
+            samples.push_back((m.cpu_millicores, m.memory_bytes));
+        }
+        
+        let mut out = String::new();
+        writeln!(out, "{}", "Pod Resource Usage:".accent().bold()).unwrap();
+        writeln!(out, "{}", "-".repeat(100)).unwrap();
+        if sparklines {
+            writeln!(out, "{:<40} {:<15} {:<8} {:<20} {:<10} {:<20}", "NAME", "NAMESPACE", "CPU", "", "MEMORY", "").unwrap();
+        } else {
+            writeln!(out, "{:<40} {:<15} {:<12} {:<12}", "NAME", "NAMESPACE", "CPU", "MEMORY").unwrap();
+        }
+        writeln!(out, "{}", "-".repeat(100)).unwrap();
+        
+        for m in &metrics {
+            let name = pad(&m.pod, 40).accent();
+            let ns = namespace_colored(&m.namespace, &pad(&m.namespace, 15));
+            if !sparklines {
+                writeln!(out, "{} {} {:<12} {:<12}", name, ns, format_cpu(m.cpu_millicores), format_memory(m.memory_bytes)).unwrap();
+                continue;
+            }
+            
+            let samples = &history[&(m.namespace.clone(), m.pod.clone())];
+            let cpu: Vec<u64> = samples.iter().map(|(cpu, _)| *cpu).collect();
+            let memory: Vec<u64> = samples.iter().map(|(_, memory)| *memory).collect();
+            writeln!(out, "{} {} {:<8} {} {:<10} {}", 
+                name, ns, format_cpu(m.cpu_millicores), pad(&sparkline(&cpu), 20).warning(), 
+                format_memory(m.memory_bytes), pad(&sparkline(&memory), 20).warning()).unwrap();
+        }
+        
+        writeln!(out, "\nTotal: {} pods", metrics.len()).unwrap();
+        (out, false)
+    })?;
+    
+    Ok(())
+}
+
+// Block characters scaled from zero to the series' own peak; zero-based so
+// small jitter on a steady pod stays flat instead of filling the range
+fn sparkline(values: &[u64]) -> String {
+    const BARS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+    let max = values.iter().copied().max().unwrap_or(0);
+    
+    values
+        .iter()
+        .map(|&v| BARS[(v * 7).checked_div(max).unwrap_or(0) as usize])
+        .collect()
+}
+
+// Block characters need a UTF-8 terminal; with colors off (--no-color,
+// NO_COLOR) the output is kept plain as well
+fn sparklines_supported() -> bool {
+    let locale = ["LC_ALL", "LC_CTYPE", "LANG"]
+        .iter()
+        .find_map(|var| std::env::var(var).ok().filter(|value| !value.is_empty()))
+        .unwrap_or_default()
+        .to_lowercase();
+    
+    colored::control::SHOULD_COLORIZE.should_colorize() && (locale.contains("utf-8") || locale.contains("utf8"))
+}
+
+// Nested pod -> container table, so the sidecar eating a pod's memory stands out
+fn show_top_containers(pod_pattern: Option<&str>, namespace: Option<String>, selector: Option<&str>, aggregate: bool, total_only: bool) -> Result<()> {
+    let metrics = top_container_metrics(pod_pattern, &namespace, selector)?;
+    
+    // Pods stay in kubectl's order, containers go heaviest first
+    let mut pods: Vec<((String, String), Vec<ContainerMetrics>)> = Vec::new();
+    for m in metrics {
+        let key = (m.namespace.clone(), m.pod.clone());
+        match pods.iter_mut().find(|(k, _)| *k == key) {
+            Some((_, containers)) => containers.push(m),
+            None => pods.push((key, vec![m])),
+        }
+    }
+    for (_, containers) in &mut pods {
+        containers.sort_by_key(|c| std::cmp::Reverse((c.memory_bytes, c.cpu_millicores)));
+    }
+    
+    println!("{}", "Container Resource Usage:".accent().bold());
+    println!("{}", "-".repeat(100));
+    println!("{:<40} {:<15} {:<12} {:<12}", "POD / CONTAINER", "NAMESPACE", "CPU", "MEMORY");
+    println!("{}", "-".repeat(100));
+    
+    if !total_only {
+        for ((ns, pod), containers) in &pods {
+            let cpu: u64 = containers.iter().map(|c| c.cpu_millicores).sum();
+            let memory: u64 = containers.iter().map(|c| c.memory_bytes).sum();
+            println!("{} {} {:<12} {:<12}", 
+                pad(pod, 40).accent(), namespace_colored(ns, &pad(ns, 15)), 
+                format_cpu(cpu), format_memory(memory));
+            
+            for c in containers {
+                println!("  {} {:<15} {:<12} {:<12}", 
+                    pad(&c.container, 38), "", format_cpu(c.cpu_millicores), format_memory(c.memory_bytes));
+            }
+        }
+    }
+    
+    if aggregate {
+        let all = pods.iter().flat_map(|(_, containers)| containers);
+        let cpu: u64 = all.clone().map(|c| c.cpu_millicores).sum();
+        let memory: u64 = all.map(|c| c.memory_bytes).sum();
+        let scope = namespace.as_deref().unwrap_or("(all)");
+        
+        if !total_only {
+            println!("{}", "-".repeat(100));
+        }
+        print_top_total(pods.len(), scope, &format_cpu(cpu), &format_memory(memory), 12);
+    }
+    
+    println!("\nTotal: {} pods", pods.len());
+    
+    Ok(())
+}
+
+// Usage next to requests/limits per container; the percentage is of the
+// limit (or the request when there's no limit), red from 90%
+fn compare_top(pod_pattern: &str, namespace: Option<String>) -> Result<()> {
+    let pod = resolve_pod(pod_pattern, namespace)?;
+    let (pod_name, ns) = pod_name_ns(&pod);
+    
+    let output = run_kubectl(&["top", "pod", &pod_name, "-n", &ns, "--containers"])?;
+    if !output.status.success() {
+        return Err(top_error(&String::from_utf8_lossy(&output.stderr)));
+    }
+    
+    // POD  NAME  CPU(cores)  MEMORY(bytes)
+    let usage: BTreeMap<String, (u64, u64)> = String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .skip(1)
+        .filter_map(|line| match line.split_whitespace().collect::<Vec<_>>().as_slice() {
+            [_, container, cpu, mem, ..] => Some((container.to_string(), (parse_cpu(cpu)?, parse_memory(mem)?))),
+            _ => None,
+        })
+        .collect();
+    
+    println!("{} Requests and limits vs usage: {} (namespace: {})", 
+        "[INFO]".accent(), pod_name.bold(), ns.muted());
+    println!("{}", "-".repeat(100));
+    println!("{:<25} {:<8} {:<8} {:<8} {:<7} {:<9} {:<9} {:<9} {:<7}", 
+        "CONTAINER", "CPU", "REQ", "LIMIT", "USE%", "MEMORY", "REQ", "LIMIT", "USE%");
+    println!("{}", "-".repeat(100));
+    
+    for container in pod["spec"]["containers"].as_array().into_iter().flatten() {
+        let name = container["name"].as_str().unwrap_or("unknown");
+        let resources = &container["resources"];
+        let quantity = |kind: &str, res: &str| resources[kind][res].as_str();
+        
+        let cpu_req = quantity("requests", "cpu").and_then(parse_cpu);
+        let cpu_lim = quantity("limits", "cpu").and_then(parse_cpu);
+        let mem_req = quantity("requests", "memory").and_then(parse_memory);
+        let mem_lim = quantity("limits", "memory").and_then(parse_memory);
+        let (cpu, mem) = match usage.get(name) {
+            Some(&(cpu, mem)) => (Some(cpu), Some(mem)),
+            None => (None, None),
+        };
+        
+        let show = |value: Option<u64>, format: fn(u64) -> String| value.map_or("-".to_string(), format);
+        
+        println!("{} {:<8} {:<8} {:<8} {} {:<9} {:<9} {:<9} {}", 
+            pad(name, 25).accent(),
+            show(cpu, format_cpu), show(cpu_req, format_cpu), show(cpu_lim, format_cpu),
+            usage_percent(cpu, cpu_lim.or(cpu_req)),
+            show(mem, format_memory), show(mem_req, format_memory), show(mem_lim, format_memory),
+            usage_percent(mem, mem_lim.or(mem_req)));
+    }
+    
+    Ok(())
+}
+
+fn usage_percent(used: Option<u64>, budget: Option<u64>) -> ColoredString {
+    let (Some(used), Some(budget)) = (used, budget.filter(|b| *b > 0)) else {
+        return format!("{:<7}", "-").normal();
+    };
+    
+    let percent = used * 100 / budget;
+    let text = format!("{:<7}", format!("{}%", percent));
+    
+    if percent >= 90 {
+        text.danger().bold()
+    } else if percent >= 75 {
+        text.warning()
+    } else {
+        text.success()
+    }
+}
+
+struct PodMetrics {
+    namespace: String,
+    pod: String,
+    cpu_millicores: u64,
+    memory_bytes: u64,
+}
+
+fn fetch_top(namespace: &Option<String>, selector: Option<&str>) -> Result<Vec<PodMetrics>> {
+    if let Some(ns) = namespace
+        && is_namespace_set(ns)
+    {
+        let mut metrics = Vec::new();
+        for ns in expand_namespaces(ns)? {
+            metrics.extend(fetch_top(&Some(ns), selector)?);
+        }
+        return Ok(metrics);
+    }
+    
+    let mut args = vec!["top", "pods"];
+    
+    if let Some(ns) = namespace {
+        args.extend(&["-n", ns]);
+    } else {
+        args.push("--all-namespaces");
+    }
+    
+    if let Some(sel) = selector {
+        args.extend(&["-l", sel]);
+    }
+    
+    let output = run_kubectl(&args)?;
+    
+    if !output.status.success() {
+        return Err(top_error(&String::from_utf8_lossy(&output.stderr)));
+    }
+    
+    let mut metrics = top_parse(&String::from_utf8_lossy(&output.stdout));
+    
+    // Namespaced output has no NAMESPACE column
+    if let Some(ns) = namespace {
+        for m in &mut metrics {
+            m.namespace = ns.clone();
+        }
+    }
+    
+    Ok(metrics)
+}
+
+fn top_container_metrics(pod_pattern: Option<&str>, namespace: &Option<String>, selector: Option<&str>) -> Result<Vec<ContainerMetrics>> {
+    let metrics = match pod_pattern {
+        Some(pattern) => {
+            let pod = resolve_pod(pattern, namespace.clone())?;
+            let (pod_name, ns) = pod_name_ns(&pod);
+            fetch_top_containers(&Some(ns), selector, Some(&pod_name))?
+        }
+        None => fetch_top_containers(namespace, selector, None)?,
+    };
+    
+    if metrics.is_empty() {
+        anyhow::bail!("No pod metrics found");
+    }
+    
+    Ok(metrics)
+}
+
+// Plain numbers instead of kubectl's 100m/50Mi, in a fixed order so
+// consecutive runs can be compared line by line
+fn print_top_json(pod_pattern: Option<&str>, namespace: Option<String>, selector: Option<&str>, containers: bool) -> Result<()> {
+    let records: Vec<Value> = if containers {
+        let mut metrics = top_container_metrics(pod_pattern, &namespace, selector)?;
+        metrics.sort_by(|a, b| (&a.namespace, &a.pod, &a.container).cmp(&(&b.namespace, &b.pod, &b.container)));
+        metrics.iter().map(|m| serde_json::json!({
+            "pod": m.pod,
+            "namespace": m.namespace,
+            "container": m.container,
+            "cpu_millicores": m.cpu_millicores,
+            "memory_bytes": m.memory_bytes,
+        })).collect()
+    } else {
+        if pod_pattern.is_some() {
+            anyhow::bail!("A pod name is only used with --compare or --containers");
+        }
+        let mut metrics = fetch_top(&namespace, selector)?;
+        if selector.is_some() && metrics.is_empty() {
+            anyhow::bail!("No pod metrics found for selector {}", selector.unwrap_or(""));
+        }
+        metrics.sort_by(|a, b| (&a.namespace, &a.pod).cmp(&(&b.namespace, &b.pod)));
+        metrics.iter().map(|m| serde_json::json!({
+            "pod": m.pod,
+            "namespace": m.namespace,
+            "cpu_millicores": m.cpu_millicores,
+            "memory_bytes": m.memory_bytes,
+        })).collect()
+    };
+    
+    println!("{}", serde_json::to_string_pretty(&records)?);
+    
+    Ok(())
+}
+
+struct ContainerMetrics {
+    namespace: String,
+    pod: String,
+    container: String,
+    cpu_millicores: u64,
+    memory_bytes: u64,
+}
+
+fn fetch_top_containers(namespace: &Option<String>, selector: Option<&str>, pod: Option<&str>) -> Result<Vec<ContainerMetrics>> {
+    if let Some(ns) = namespace
+        && is_namespace_set(ns)
+    {
+        let mut metrics = Vec::new();
+        for ns in expand_namespaces(ns)? {
+            metrics.extend(fetch_top_containers(&Some(ns), selector, pod)?);
+        }
+        return Ok(metrics);
+    }
+    
+    let mut args = vec!["top", "pods"];
+    args.extend(pod);
+    
+    if let Some(ns) = namespace {
+        args.extend(&["-n", ns]);
+    } else {
+        args.push("--all-namespaces");
+    }
+    
+    if let Some(sel) = selector {
+        args.extend(&["-l", sel]);
+    }
+    args.push("--containers");
+    
+    let output = run_kubectl(&args)?;
+    
+    if !output.status.success() {
+        return Err(top_error(&String::from_utf8_lossy(&output.stderr)));
+    }
+    
+    let mut metrics = top_containers_parse(&String::from_utf8_lossy(&output.stdout));
+    
+    if let Some(ns) = namespace {
+        for m in &mut metrics {
+            m.namespace = ns.clone();
+        }
+    }
+    
+    Ok(metrics)
+}
+
+// Same as top_parse for `kubectl top pods --containers`, which adds a
+// container NAME column after POD
+fn top_containers_parse(text: &str) -> Vec<ContainerMetrics> {
+    let mut lines = text.lines();
+    let has_namespace = lines
+        .next()
+        .map(|header| header.trim_start().starts_with("NAMESPACE"))
+        .unwrap_or(false);
+    
+    lines
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            let (namespace, pod, container, cpu, mem) = match (has_namespace, fields.as_slice()) {
+                (true, [ns, pod, container, cpu, mem, ..]) => (*ns, *pod, *container, *cpu, *mem),
+                (false, [pod, container, cpu, mem, ..]) => ("", *pod, *container, *cpu, *mem),
+                _ => return None,
+            };
+            
+            Some(ContainerMetrics {
+                namespace: namespace.to_string(),
+                pod: pod.to_string(),
+                container: container.to_string(),
+                cpu_millicores: parse_cpu(cpu)?,
+                memory_bytes: parse_memory(mem)?,
+            })
+        })
+        .collect()
+}
+
+// Parses `kubectl top pods` output (with headers). The NAMESPACE column is
+// optional; when absent, namespace is left empty for the caller to fill.
+fn top_parse(text: &str) -> Vec<PodMetrics> {
+    let mut lines = text.lines();
+    let has_namespace = lines
+        .next()
+        .map(|header| header.trim_start().starts_with("NAMESPACE"))
+        .unwrap_or(false);
+    
+    lines
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            let (namespace, pod, cpu, mem) = match (has_namespace, fields.as_slice()) {
+                (true, [ns, pod, cpu, mem, ..]) => (*ns, *pod, *cpu, *mem),
+                (false, [pod, cpu, mem, ..]) => ("", *pod, *cpu, *mem),
+                _ => return None,
+            };
+            
+            Some(PodMetrics {
+                namespace: namespace.to_string(),
+                pod: pod.to_string(),
+                cpu_millicores: parse_cpu(cpu)?,
+                memory_bytes: parse_memory(mem)?,
+            })
+        })
+        .collect()
+}
+
+// Turns kubectl top's stderr into an actionable error
+fn top_error(stderr: &str) -> anyhow::Error {
+    let stderr = stderr.trim();
+    let lower = stderr.to_lowercase();
+    
+    if lower.contains("metrics api not available") || lower.contains("metrics not available") {
+        eprintln!("{} metrics-server is not installed or not ready in this cluster", "[ERROR]".danger());
+        eprintln!("{} Install it with:", "[INFO]".accent());
+        eprintln!("  kubectl apply -f https://github.com/kubernetes-sigs/metrics-server/releases/latest/download/components.yaml");
+        eprintln!("{} See https://github.com/kubernetes-sigs/metrics-server for details", "[INFO]".accent());
+        KdbgError::MetricsUnavailable.into()
+    } else if lower.contains("forbidden") {
+        anyhow::anyhow!("Not allowed to read pod metrics: {}", stderr)
+    } else if is_namespace_not_found(stderr) {
+        anyhow::anyhow!("Namespace not found: {}", stderr)
+    } else {
+        anyhow::anyhow!("Failed to get resource usage: {}", stderr)
+    }
+}
+
+// Only kubectl's `namespaces "NAME" not found`: a missing pod or API
+// resource says "not found" too
+fn is_namespace_not_found(stderr: &str) -> bool {
+    stderr.split("namespaces \"").skip(1).any(|rest| {
+        rest.split_once('"').is_some_and(|(name, after)| !name.is_empty() && after.starts_with(" not found"))
+    })
+}
+
+fn show_top_samples(
+    namespace: Option<String>,
+    selector: Option<&str>,
+    samples: u32,
+    interval: u64,
+    aggregate: bool,
+    total_only: bool,
+) -> Result<()> {
+    use std::collections::BTreeMap;
+    use std::time::Duration;
+    
+    if samples == 0 {
+        anyhow::bail!("--samples must be at least 1");
+    }
+    
+    println!("{} Taking {} samples every {}s...", "[INFO]".accent(), samples, interval);
+    
+    // (namespace, pod) -> [(cpu millicores, memory bytes)]
+    let mut usage: BTreeMap<(String, String), Vec<(u64, u64)>> = BTreeMap::new();
+    
+    for i in 0..samples {
+        if i > 0 {
+            std::thread::sleep(Duration::from_secs(interval));
+        }
+        
+        for m in fetch_top(&namespace, selector)? {
+            usage.entry((m.namespace, m.pod)).or_default().push((m.cpu_millicores, m.memory_bytes));
+        }
+        
+        eprint!("\r{} Sample {}/{}", "[INFO]".accent(), i + 1, samples);
+    }
+    eprintln!();
+    
+    println!("{}", "Pod Resource Usage (min/avg/max):".accent().bold());
+    println!("{}", "-".repeat(100));
+    println!("{:<40} {:<15} {:<20} {:<20}", "NAME", "NAMESPACE", "CPU", "MEMORY");
+    println!("{}", "-".repeat(100));
+    
+    let (mut cpu_total, mut mem_total) = (0, 0);
+    
+    for ((ns, name), values) in &usage {
+        let cpu: Vec<u64> = values.iter().map(|(c, _)| *c).collect();
+        let mem: Vec<u64> = values.iter().map(|(_, m)| *m).collect();
+        
+        let (cpu_min, cpu_avg, cpu_max) = min_avg_max(&cpu);
+        let (mem_min, mem_avg, mem_max) = min_avg_max(&mem);
+        cpu_total += cpu_avg;
+        mem_total += mem_avg;
+        
+        if total_only {
+            continue;
+        }
+        
+        let cpu_str = format!("{}/{}/{}", format_cpu(cpu_min), format_cpu(cpu_avg), format_cpu(cpu_max));
+        let mem_str = format!("{}/{}/{}", format_memory(mem_min), format_memory(mem_avg), format_memory(mem_max));
+        
+        let cpu_colored = if is_bursty(cpu_min, cpu_avg, cpu_max) { cpu_str.danger() } else { cpu_str.normal() };
+        let mem_colored = if is_bursty(mem_min, mem_avg, mem_max) { mem_str.danger() } else { mem_str.normal() };
+        
+        println!("{:<40} {:<15} {:<20} {:<20}", 
+            name.accent(), namespace_colored(ns, ns), cpu_colored, mem_colored);
+    }
+    
+    if aggregate {
+        if !total_only {
+            println!("{}", "-".repeat(100));
+        }
+        let scope = namespace.as_deref().unwrap_or("(all)");
+        print_top_total(usage.len(), scope, 
+            &format!("{} avg", format_cpu(cpu_total)), &format!("{} avg", format_memory(mem_total)), 20);
+    }
+    
+    println!("\nTotal: {} pods", usage.len());
+    
+    Ok(())
+}
+
+// Reverse video sets the footer apart from the pod rows whatever their colors
+fn print_top_total(pods: usize, scope: &str, cpu: &str, memory: &str, width: usize) {
+    let row = format!("{} {} {} {}", 
+        pad(&format!("TOTAL ({} pods)", pods), 40), pad(scope, 15), pad(cpu, width), pad(memory, width));
+    println!("{}", row.bold().reversed());
+}
+
+fn min_avg_max(values: &[u64]) -> (u64, u64, u64) {
+    let min = values.iter().copied().min().unwrap_or(0);
+    let max = values.iter().copied().max().unwrap_or(0);
+    let avg = if values.is_empty() { 0 } else { values.iter().sum::<u64>() / values.len() as u64 };
+    (min, avg, max)
+}
+
+// A pod is bursty when its range spans more than half its average
+fn is_bursty(min: u64, avg: u64, max: u64) -> bool {
+    avg > 0 && (max - min) * 2 > avg
+}
+
+// CPU quantity to millicores: "250m", "1", "0.5", "1500000n", "300u"
+fn parse_cpu(value: &str) -> Option<u64> {
+    if let Some(n) = value.strip_suffix('n') {
+        n.parse::<u64>().ok().map(|n| n / 1_000_000)
+    } else if let Some(u) = value.strip_suffix('u') {
+        u.parse::<u64>().ok().map(|u| u / 1_000)
+    } else if let Some(m) = value.strip_suffix('m') {
+        m.parse().ok()
+    } else {
+        parse_quantity_number(value).map(|cores| (cores * 1000.0).round() as u64)
+    }
+}
+
+// `as u64` would quietly turn "-1", "NaN" or "inf" into a number
+fn parse_quantity_number(value: &str) -> Option<f64> {
+    value.parse::<f64>().ok().filter(|n| n.is_finite() && *n >= 0.0)
+}
+
+// Memory quantity to bytes: binary (Ki, Mi, Gi, Ti), decimal (k, M, G, T) or plain
+fn parse_memory(value: &str) -> Option<u64> {
+    let units = [
+        ("Ki", 1u64 << 10), ("Mi", 1 << 20), ("Gi", 1 << 30), ("Ti", 1 << 40),
+        ("k", 1_000), ("M", 1_000_000), ("G", 1_000_000_000), ("T", 1_000_000_000_000),
+    ];
+    
+    for (suffix, factor) in units {
+        if let Some(n) = value.strip_suffix(suffix) {
+            return parse_quantity_number(n).map(|n| (n * factor as f64) as u64);
+        }
+    }
+    
+    value.parse().ok()
+}
+
+fn format_cpu(millicores: u64) -> String {
+    format!("{}m", millicores)
+}
+
+fn format_memory(bytes: u64) -> String {
+    if bytes >= 1 << 30 {
+        format!("{:.1}Gi", bytes as f64 / (1u64 << 30) as f64)
+    } else {
+        format!("{}Mi", bytes >> 20)
+    }
+}
+
+fn parse_forward_target(target: &[String], has_selector: bool) -> Result<(Option<String>, u16, u16)> {
+    let (pod, ports) = if has_selector {
+        (None, target)
+    } else {
+        match target.split_first() {
+            Some((pod, ports)) => (Some(pod.clone()), ports),
+            None => anyhow::bail!("Missing pod name"),
+        }
+    };
+    
+    let (local, remote) = match ports {
+        [spec] => spec
+            .split_once(':')
+            .ok_or_else(|| anyhow::anyhow!("Invalid port spec '{}' (expected LOCAL:POD)", spec))?,
+        [local, remote] => (local.as_str(), remote.as_str()),
+        _ => anyhow::bail!("Expected LOCAL_PORT POD_PORT or LOCAL:POD"),
+    };
+    
+    let local_port = local.parse().map_err(|_| anyhow::anyhow!("Invalid local port '{}'", local))?;
+    let pod_port = remote.parse().map_err(|_| anyhow::anyhow!("Invalid pod port '{}'", remote))?;
+    
+    Ok((pod, local_port, pod_port))
+}
+
+fn pick_ready_pod(selector: &str, namespace: &Option<String>, avoid: Option<&str>) -> Result<(String, String)> {
+    let pods = fetch_pods(namespace, Some(selector))?;
+    let ready: Vec<_> = pods.iter().filter(|pod| is_pod_ready(pod)).collect();
+    
+    if ready.is_empty() {
+        anyhow::bail!("No ready pods found matching selector '{}'", selector);
+    }
+    
+    // Prefer a different pod than the one that just died, if there is one
+    let candidates: Vec<_> = match avoid {
+        Some(name) if ready.len() > 1 => ready
+            .into_iter()
+            .filter(|pod| pod["metadata"]["name"].as_str() != Some(name))
+            .collect(),
+        _ => ready,
+    };
+    
+    // Least-restarted pods first, random pick among ties
+    let fewest = candidates.iter().map(|pod| restart_count(pod)).min().unwrap_or(0);
+    let best: Vec<_> = candidates.into_iter().filter(|pod| restart_count(pod) == fewest).collect();
+    
+    let seed = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.subsec_nanos() as usize)
+        .unwrap_or(0);
+    Ok(pod_name_ns(best[seed % best.len()]))
+}
+
+fn port_forward(
+    pod_pattern: Option<&str>,
+    selector: Option<&str>,
+    local_port: u16,
+    pod_port: u16,
+    namespace: Option<String>,
+    reconnect: bool,
+    idle_timeout: Option<std::time::Duration>,
+) -> Result<()> {
+    use std::time::Duration;
+    
+    // Selector mode always supervises the forward so it can fail over
+    let reconnect = reconnect || selector.is_some();
+    let mut last_pod: Option<String> = None;
+    
+    loop {
+        let resolved = match (selector, pod_pattern) {
+            (Some(sel), _) => pick_ready_pod(sel, &namespace, last_pod.as_deref()),
+            (None, Some(pattern)) => find_pod(pattern, namespace.clone()),
+            (None, None) => anyhow::bail!("Missing pod name or --selector"),
+        };
+        
+        let (pod_name, ns) = match resolved {
+            Ok(found) => found,
+            Err(e) if last_pod.is_some() => {
+                eprintln!("{} {} (retrying in 2s)", "[WARN]".warning(), e);
+                std::thread::sleep(Duration::from_secs(2));
+                continue;
+            }
+            Err(e) => return Err(e),
+        };
+        
+        println!("{} Port forwarding: localhost:{} -> {}:{} (namespace: {})", 
+            "[INFO]".accent(), local_port, pod_name.bold(), pod_port, ns.muted());
+        println!("{} Press Ctrl+C to stop", "[INFO]".warning());
+        println!("{}", "-".repeat(100));
+        
+        let succeeded = match idle_timeout {
+            Some(timeout) => match supervise_forward(&pod_name, &ns, local_port, pod_port, timeout, reconnect) {
+                Ok(succeeded) => succeeded,
+                Err(e) if reconnect => {
+                    eprintln!("{} {}", "[WARN]".warning(), e);
+                    false
+                }
+                Err(e) => return Err(e),
+            },
+            None => {
+                let ports = format!("{}:{}", local_port, pod_port);
+                let mut args = vec!["port-forward", &pod_name, &ports, "-n", &ns];
+                args.extend(extra_kubectl_args());
+                kubectl(&args).status()?.success()
+            }
+        };
+        
+        if !reconnect {
+            if !succeeded {
+                anyhow::bail!("Port forwarding failed");
+            }
+            return Ok(());
+        }
+        
+        eprintln!("{} Port forward to {} ended, reconnecting...", "[WARN]".warning(), pod_name);
+        std::thread::sleep(Duration::from_secs(1));
+        last_pod = Some(pod_name);
+    }
+}
+
+// One kubectl port-forward per ready pod, listening on a port kubectl picks
+struct Backend {
+    pod: String,
+    namespace: String,
+    port: u16,
+    child: std::process::Child,
+}
+
+// Approximates a Service locally: a listener on the requested port hands each
+// incoming connection to the next pod's port-forward in turn
+fn round_robin_forward(selector: &str, local_port: u16, pod_port: u16, namespace: Option<String>) -> Result<()> {
+    use std::net::{TcpListener, TcpStream};
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+    
+    let listener = TcpListener::bind(("127.0.0.1", local_port))
+        .map_err(|e| anyhow::anyhow!("Can't listen on localhost:{}: {}", local_port, e))?;
+    
+    let backends = Arc::new(Mutex::new(Vec::new()));
+    sync_backends(&backends, selector, &namespace, pod_port)?;
+    let count = backends.lock().unwrap().len();
+    if count == 0 {
+        anyhow::bail!("No ready pods found matching selector '{}'", selector);
+    }
+    
+    println!("{} Round-robin forwarding: localhost:{} -> {} ready pods, port {} (selector: {})", 
+        "[INFO]".accent(), local_port, count, pod_port, selector.bold());
+    println!("{} Press Ctrl+C to stop", "[INFO]".warning());
+    println!("{}", "-".repeat(100));
+    
+    // Pods come and go, so the rotation follows the ready pods
+    {
+        let backends = Arc::clone(&backends);
+        let selector = selector.to_string();
+        std::thread::spawn(move || loop {
+            std::thread::sleep(Duration::from_secs(ROUND_ROBIN_REFRESH_SECS));
+            if let Err(e) = sync_backends(&backends, &selector, &namespace, pod_port) {
+                eprintln!("{} {}", "[WARN]".warning(), e);
+            }
+        });
+    }
+    
+    let mut next = 0usize;
+    for client in listener.incoming() {
+        let client = match client {
+            Ok(client) => client,
+            Err(e) => {
+                eprintln!("{} {}", "[WARN]".warning(), e);
+                continue;
+            }
+        };
+        
+        // Skips over forwards that refuse the connection until the next refresh drops them
+        let upstream = {
+            let backends = backends.lock().unwrap();
+            (0..backends.len()).find_map(|i| {
+                let index = (next + i) % backends.len();
+                let backend = &backends[index];
+                TcpStream::connect(("127.0.0.1", backend.port))
+                    .ok()
+                    .map(|stream| (stream, backend.pod.clone(), index))
+            })
+        };
+        
+        match upstream {
+            Some((upstream, pod, index)) => {
+                next = index + 1;
+                println!("{} Connection -> {}", "[INFO]".accent(), pod.bold());
+                proxy_connection(client, upstream, None);
+            }
+            None => eprintln!("{} No pod accepted the connection, dropping it", "[WARN]".warning()),
+        }
+    }
+    
+    Ok(())
+}
+
+// Drops forwards whose kubectl exited or whose pod stopped being ready, and
+// starts forwards for newly ready pods
+fn sync_backends(
+    backends: &std::sync::Mutex<Vec<Backend>>,
+    selector: &str,
+    namespace: &Option<String>,
+    pod_port: u16,
+) -> Result<()> {
+    let ready: Vec<(String, String)> = fetch_pods(namespace, Some(selector))?
+        .iter()
+        .filter(|pod| is_pod_ready(pod))
+        .map(pod_name_ns)
+        .collect();
+    
+    let missing: Vec<_> = {
+        let mut current = backends.lock().unwrap();
+        current.retain_mut(|backend| {
+            let running = matches!(backend.child.try_wait(), Ok(None));
+            let ready = ready.iter().any(|(name, ns)| *name == backend.pod && *ns == backend.namespace);
+            if !(running && ready) {
+                let _ = backend.child.kill();
+                let _ = backend.child.wait();
+                println!("{} Removed {} from rotation", "[WARN]".warning(), backend.pod);
+            }
+            running && ready
+        });
+        ready
+            .into_iter()
+            .filter(|(name, ns)| !current.iter().any(|b| b.pod == *name && b.namespace == *ns))
+            .collect()
+    };
+    
+    // Started without holding the lock so connections keep flowing meanwhile
+    for (pod, ns) in missing {
+        match start_backend(&pod, &ns, pod_port) {
+            Ok(backend) => {
+                println!("{} Added {} to rotation (namespace: {})", 
+                    "[INFO]".accent(), pod.bold(), namespace_colored(&ns, &ns));
+                backends.lock().unwrap().push(backend);
+            }
+            Err(e) => eprintln!("{} {}", "[WARN]".warning(), e),
+        }
+    }
+    
+    Ok(())
+}
+
+fn start_backend(pod: &str, ns: &str, pod_port: u16) -> Result<Backend> {
+    use std::io::{BufRead, BufReader};
+    
+    let ports = format!(":{}", pod_port);
+    let mut args = vec!["port-forward", pod, &ports, "-n", ns, "--address", "127.0.0.1"];
+    args.extend(extra_kubectl_args());
+    let mut child = kubectl(&args)
+        .stdout(Stdio::piped())
+        .spawn()?;
+    
+    let mut lines = BufReader::new(child.stdout.take().unwrap()).lines();
+    
+    // kubectl reports the port it picked as "Forwarding from 127.0.0.1:41234 -> 80"
+    let port = lines.by_ref().map_while(Result::ok).find_map(|line| {
+        line.strip_prefix("Forwarding from 127.0.0.1:")?
+            .split_whitespace()
+            .next()?
+            .parse()
+            .ok()
+    });
+    
+    let Some(port) = port else {
+        let _ = child.kill();
+        let _ = child.wait();
+        anyhow::bail!("Port forward to {} didn't start", pod);
+    };
+    
+    // Keep reading so kubectl never blocks on a full pipe
+    std::thread::spawn(move || lines.map_while(Result::ok).for_each(drop));
+    
+    Ok(Backend { pod: pod.to_string(), namespace: ns.to_string(), port, child })
+}
+
+// Pipes both directions on their own threads; with an activity clock, every
+// chunk that goes through either way winds it forward
+fn proxy_connection(
+    client: std::net::TcpStream,
+    upstream: std::net::TcpStream,
+    activity: Option<&std::sync::Arc<std::sync::Mutex<std::time::Instant>>>,
+) {
+    use std::io::{Read, Write};
+    use std::net::{Shutdown, TcpStream};
+    use std::time::Instant;
+    
+    let pipe = |mut from: TcpStream, mut to: TcpStream| {
+        let activity = activity.cloned();
+        std::thread::spawn(move || {
+            let mut buf = [0u8; 16 * 1024];
+            loop {
+                let n = match from.read(&mut buf) {
+                    Ok(0) | Err(_) => break,
+                    Ok(n) => n,
+                };
+                if to.write_all(&buf[..n]).is_err() {
+                    break;
+                }
+                if let Some(activity) = &activity {
+                    *activity.lock().unwrap() = Instant::now();
+                }
+            }
+            let _ = to.shutdown(Shutdown::Write);
+        });
+    };
+    
+    match (client.try_clone(), upstream.try_clone()) {
+        (Ok(client_read), Ok(upstream_read)) => {
+            pipe(client_read, upstream);
+            pipe(upstream_read, client);
+        }
+        (Err(e), _) | (_, Err(e)) => eprintln!("{} {}", "[WARN]".warning(), e),
+    }
+}
+
+// Listens on the local port itself and proxies each connection to a kubectl
+// port-forward on a port kubectl picks, so activity means bytes actually moving
+// rather than kubectl logging a new connection. When idle for too long, either
+// warns or (with reconnect) kills the forward so the caller re-picks.
+fn supervise_forward(
+    pod: &str,
+    ns: &str,
+    local_port: u16,
+    pod_port: u16,
+    idle_timeout: std::time::Duration,
+    reconnect: bool,
+) -> Result<bool> {
+    use std::net::{TcpListener, TcpStream};
+    use std::sync::{Arc, Mutex};
+    use std::time::{Duration, Instant};
+    
+    let listener = TcpListener::bind(("127.0.0.1", local_port))
+        .map_err(|e| anyhow::anyhow!("Can't listen on localhost:{}: {}", local_port, e))?;
+    listener.set_nonblocking(true)?;
+    
+    let Backend { port, mut child, .. } = start_backend(pod, ns, pod_port)?;
+    let last_activity = Arc::new(Mutex::new(Instant::now()));
+    
+    loop {
+        match listener.accept() {
+            Ok((client, _)) => {
+                *last_activity.lock().unwrap() = Instant::now();
+                let upstream = client
+                    .set_nonblocking(false)
+                    .and_then(|_| TcpStream::connect(("127.0.0.1", port)));
+                match upstream {
+                    Ok(upstream) => proxy_connection(client, upstream, Some(&last_activity)),
+                    Err(e) => eprintln!("{} {}", "[WARN]".warning(), e),
+                }
+                continue;
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::WouldBlock => {}
+            Err(e) => eprintln!("{} {}", "[WARN]".warning(), e),
+        }
+        
+        if let Some(status) = child.try_wait()? {
+            return Ok(status.success());
+        }
+        
+        let idle = last_activity.lock().unwrap().elapsed();
+        if idle >= idle_timeout {
+            if reconnect {
+                eprintln!("{} No traffic for {}s, restarting forward", 
+                    "[WARN]".warning(), idle.as_secs());
+                child.kill()?;
+                return Ok(child.wait()?.success());
+            }
+            
+            eprintln!("{} No traffic for {}s, the forward may be stale", 
+                "[WARN]".warning(), idle.as_secs());
+            *last_activity.lock().unwrap() = Instant::now();
+        }
+        
+        std::thread::sleep(Duration::from_millis(50));
+    }
+}
+
+// Parses durations like "30s", "5m", "2h", "1d" (plain numbers are seconds)
+fn parse_duration(value: &str) -> Result<std::time::Duration, String> {
+    let value = value.trim();
+    let split = value.find(|c: char| !c.is_ascii_digit()).unwrap_or(value.len());
+    let (number, unit) = value.split_at(split);
+    
+    let number: u64 = number
+        .parse()
+        .map_err(|_| format!("invalid duration '{}'", value))?;
+    
+    let factor = match unit {
+        "" | "s" => 1,
+        "m" => 60,
+        "h" => 3600,
+        "d" => 86400,
+        _ => return Err(format!("invalid duration unit in '{}' (use s, m, h or d)", value)),
+    };
+    let secs = number
+        .checked_mul(factor)
+        .ok_or_else(|| format!("invalid duration '{}' (too long)", value))?;
+    
+    Ok(std::time::Duration::from_secs(secs))
+}
+
+const SHELL_EXIT_HINT: &str = "Type exit or press Ctrl+D to leave; the container keeps running";
+
+// Unlike exec, attach joins the container's main process, so the way out
+// matters: Ctrl+C or exit goes to that process and can stop the container
+fn attach_pod(pod_pattern: &str, namespace: Option<String>, container: Option<&str>, timeout: std::time::Duration) -> Result<()> {
+    let pod = resolve_pod(pod_pattern, namespace)?;
+    let (pod_name, ns) = pod_name_ns(&pod);
+    
+    let container = match container {
+        Some(name) => name.to_string(),
+        None => default_container_name(&pod)
+            .ok_or_else(|| anyhow::anyhow!("Pod {} has no containers", pod_name))?,
+    };
+    let Some(spec) = pod["spec"]["containers"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .find(|c| c["name"].as_str() == Some(&container))
+    else {
+        anyhow::bail!("Pod {} has no container '{}' (containers: {})", pod_name, container, pod_containers(&pod).join(", "));
+    };
+    
+    // Without stdin/tty in the spec there is nothing to type into; attach just streams output
+    let stdin = spec["stdin"].as_bool().unwrap_or(false);
+    let tty = spec["tty"].as_bool().unwrap_or(false);
+    
+    println!("{} Attaching to pod: {} (container: {}, namespace: {})", 
+        "[INFO]".accent(), pod_name.bold(), container.warning(), ns.muted());
+    
+    wait_for_container_running(&pod_name, &ns, &container, timeout)?;
+    
+    if stdin {
+        println!("{} Input goes to the container's main process: Ctrl+C or exit there can stop the container", "[WARN]".warning());
+        println!("{} Detach with Ctrl+P Ctrl+Q (where the runtime supports it) or close the terminal", "[INFO]".accent());
+    } else {
+        println!("{} Container has no stdin, streaming its output; Ctrl+C detaches without stopping it", "[INFO]".accent());
+    }
+    println!("{}", "-".repeat(100));
+    
+    let running_timeout = format!("--pod-running-timeout={}s", timeout.as_secs().max(1));
+    let mut args = vec!["attach", &pod_name, "-n", &ns, "-c", &container, &running_timeout];
+    if stdin {
+        args.push("-i");
+    }
+    if tty {
+        args.push("-t");
+    }
+    args.extend(extra_kubectl_args());
+    
+    let status = kubectl(&args)
+        .stdin(Stdio::inherit())
+        .stdout(Stdio::inherit())
+        .stderr(Stdio::inherit())
+        .status()?;
+    
+    if !status.success() {
+        anyhow::bail!("Failed to attach to {}", pod_name);
+    }
+    
+    Ok(())
+}
+
+// A just-created pod, or one in a restart back-off, has nothing to attach to
+// yet; kubectl would fail right away, so poll until the container runs
+fn wait_for_container_running(pod_name: &str, ns: &str, container: &str, timeout: std::time::Duration) -> Result<()> {
+    use std::time::{Duration, Instant};
+    
+    let start = Instant::now();
+    let mut announced = false;
+    
+    loop {
+        let output = run_kubectl(&["get", "pod", pod_name, "-n", ns, "-o", "json"])?;
+        if !output.status.success() {
+            anyhow::bail!("Failed to get pod {}: {}", pod_name, String::from_utf8_lossy(&output.stderr).trim());
+        }
+        let pod: Value = serde_json::from_slice(&output.stdout)?;
+        let status = container_status(&pod, Some(container));
+        
+        if status.is_some_and(|s| s["state"]["running"].is_object()) {
+            return Ok(());
+        }
+        
+        if start.elapsed() >= timeout {
+            anyhow::bail!("Container {} still isn't running after {}s", container, timeout.as_secs());
+        }
+        
+        if !announced {
+            let reason = status
+                .and_then(|s| s["state"]["waiting"]["reason"].as_str().or_else(|| s["state"]["terminated"]["reason"].as_str()))
+                .unwrap_or("not started");
+            println!("{} Container {} isn't running yet ({}), waiting up to {}s...", 
+                "[INFO]".warning(), container, reason, timeout.as_secs());
+            announced = true;
+        }
+        
+        std::thread::sleep(Duration::from_secs(2));
+    }
+}
+
+const DEFAULT_SHELLS: &[&str] = &["/bin/bash", "/bin/ash", "/bin/sh"];
+
+fn shell_pod(pod_pattern: &str, namespace: Option<String>) -> Result<()> {
+    let pod = resolve_pod(pod_pattern, namespace)?;
+    let (pod_name, ns) = pod_name_ns(&pod);
+    
+    println!("{} Opening shell in pod: {} (namespace: {})", 
+        "[INFO]".accent(), pod_name.bold(), ns.muted());
+    println!("{} {}", "[INFO]".accent(), SHELL_EXIT_HINT);
+    println!("{}", "-".repeat(100));
+    
+    // Try the shells in order (bash, ash, sh unless configured), starting
+    // with the one that worked for this image before
+    let image = default_container_status(&pod).and_then(container_image_key);
+    let mut shells = config().get_list("shells")
+        .filter(|shells| !shells.is_empty())
+        .unwrap_or_else(|| DEFAULT_SHELLS.iter().map(|shell| shell.to_string()).collect());
+    if let Some(cached) = image.as_deref().and_then(cached_shell) {
+        shells.sort_by_key(|shell| *shell != cached);
+    }
+    let remember = |shell: &str| {
+        if let Some(image) = &image {
+            remember_shell(image, shell);
+        }
+    };
+    
+    for (i, shell) in shells.iter().enumerate() {
+        // Flags after kubectl's own -- would become part of the container command
+        let mut args = vec!["exec", "-it", &pod_name, "-n", &ns];
+        args.extend(extra_kubectl_args());
+        args.extend(&["--", shell.as_str()]);
+        let mut cmd = kubectl(&args);
+        
+        // Inherit stdin/stdout/stderr for interactive shell
+        cmd.stdin(Stdio::inherit())
+           .stdout(Stdio::inherit())
+           .stderr(Stdio::null()); // Suppress error messages when trying shells
+        
+        let status = cmd.status()?;
+        
+        if status.success() {
+            remember(shell);
+            return Ok(());
+        }
+        
+        // Retry the last shell with stderr visible, so the real error shows
+        if i == shells.len() - 1 {
+            let mut args = vec!["exec", "-it", &pod_name, "-n", &ns];
+            args.extend(extra_kubectl_args());
+            args.extend(&["--", shell.as_str()]);
+            let mut cmd = kubectl(&args);
+            cmd.stdin(Stdio::inherit())
+               .stdout(Stdio::inherit())
+               .stderr(Stdio::inherit());
+            
+            let status = cmd.status()?;
+            if status.success() {
+                remember(shell);
+                return Ok(());
+            }
+        }
+    }
+    
+    anyhow::bail!("Failed to open shell (tried {}); set `shells` in the config file to try others", shells.join(", "))
+}
+
+fn debug_pod(
+    image: &str,
+    namespace: &str,
+    ephemeral: Option<&str>,
+    profile: Option<&str>,
+    copy_env_from: Option<&str>,
+    keep: bool,
+    pull_policy: Option<&str>,
+) -> Result<()> {
+    use std::time::{SystemTime, UNIX_EPOCH};
+    
+    if let Some(profile) = profile {
+        check_debug_profile(profile)?;
+    }
+    
+    if let Some(pod_pattern) = ephemeral {
+        return debug_ephemeral(pod_pattern, image, namespace, profile, pull_policy);
+    }
+    
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs();
+    
+    let pod_name = format!("debug-{}", timestamp);
+    
+    println!("{} Creating debug pod: {} (image: {}, namespace: {})", 
+        "[INFO]".accent(), pod_name.bold(), image.warning(), namespace.muted());
+    if keep {
+        println!("{} Pod will be kept after you exit the shell", "[INFO]".warning());
+    } else {
+        println!("{} Pod will be deleted when you exit the shell", "[INFO]".warning());
+    }
+    
+    // Secret/ConfigMap references only resolve in the source pod's namespace,
+    // so the source is looked up in the debug namespace
+    let overrides = match copy_env_from {
+        Some(pattern) => {
+            let source = resolve_pod(pattern, Some(namespace.to_string()))?;
+            let (source_name, _) = pod_name_ns(&source);
+            let container = &source["spec"]["containers"][0];
+            
+            let env_count = container["env"].as_array().map_or(0, |e| e.len());
+            let env_from_count = container["envFrom"].as_array().map_or(0, |e| e.len());
+            println!("{} Copying {} env vars and {} envFrom sources from {}", 
+                "[INFO]".accent(), env_count, env_from_count, source_name.bold());
+            
+            let patch = serde_json::json!({
+                "spec": {
+                    "containers": [{
+                        "name": pod_name,
+                        "env": container["env"].as_array().cloned().unwrap_or_default(),
+                        "envFrom": container["envFrom"].as_array().cloned().unwrap_or_default(),
+                    }]
+                }
+            });
+            Some(patch.to_string())
+        }
+        None => None,
+    };
+    println!("{}", "-".repeat(100));
+    
+    let mut args = vec![
+        "run",
+        &pod_name,
+        "--image", image,
+        "-n", namespace,
+        "--restart=Never",
+    ];
+    
+    if let Some(policy) = pull_policy {
+        args.extend(&["--image-pull-policy", policy]);
+    }
+    
+    // Strategic merge so the patch only adds env to kubectl run's generated container
+    if let Some(overrides) = &overrides {
+        args.extend(&["--override-type=strategic", "--overrides", overrides]);
+    }
+    
+    if keep {
+        return debug_pod_kept(&pod_name, namespace, args);
+    }
+    
+    args.extend(&["--rm", "-it", "--", "/bin/sh"]);
+    
+    // Create pod
+    let output = kubectl(&args)
+        .stdin(Stdio::inherit())
+        .stdout(Stdio::inherit())
+        .stderr(Stdio::inherit())
+        .status()?;
+    
+    if !output.success() {
+        anyhow::bail!("Failed to create debug pod");
+    }
+    
+    Ok(())
+}
+
+// A kept pod runs an idle process instead of the shell, so leaving the shell
+// doesn't complete the pod; the shell is an exec into it
+fn debug_pod_kept(pod_name: &str, namespace: &str, mut run_args: Vec<&str>) -> Result<()> {
+    run_args.extend(&["--", "tail", "-f", "/dev/null"]);
+    
+    if !kubectl(&run_args).status()?.success() {
+        anyhow::bail!("Failed to create debug pod");
+    }
+    
+    let pod_ref = format!("pod/{}", pod_name);
+    let status = kubectl(&["wait", &pod_ref, "-n", namespace, "--for=condition=Ready", "--timeout=120s"]).status()?;
+    if !status.success() {
+        anyhow::bail!("Debug pod {} didn't become ready", pod_name);
+    }
+    
+    let status = kubectl(&["exec", "-it", pod_name, "-n", namespace, "--", "/bin/sh"])
+        .stdin(Stdio::inherit())
+        .stdout(Stdio::inherit())
+        .stderr(Stdio::inherit())
+        .status()?;
+    
+    println!("{}", "-".repeat(100));
+    println!("{} Debug pod {} is still running", "[INFO]".accent(), pod_name.bold());
+    println!("  Re-enter: kubectl exec -it {} -n {} -- /bin/sh", pod_name, namespace);
+    println!("  Delete:   kubectl delete pod {} -n {}", pod_name, namespace);
+    
+    if !status.success() {
+        anyhow::bail!("Shell exited with an error");
+    }
+    
+    Ok(())
+}
+
+// Unlike debug --copy, the clone keeps its command and runs until deleted.
+// Without an owner the controller neither adopts nor deletes it, and without
+// the original labels no Service sends it traffic
+fn recreate_pod(pod_pattern: &str, namespace: Option<String>, yes: bool) -> Result<()> {
+    use std::io::Write;
+    
+    let pod = resolve_pod(pod_pattern, namespace)?;
+    let (pod_name, ns) = pod_name_ns(&pod);
+    
+    let timestamp = chrono::Utc::now().timestamp();
+    let mut clone_name = format!("{}-clone-{}", pod_name, timestamp);
+    if clone_name.len() > 63 {
+        // Pod names become hostnames, which are capped at 63 characters
+        let suffix = format!("-clone-{}", timestamp);
+        clone_name = format!("{}{}", pod_name[..63 - suffix.len()].trim_end_matches('-'), suffix);
+    }
+    let manifest = standalone_pod(&pod, &clone_name);
+    
+    println!("{} Cloning pod: {} -> {} (namespace: {})", 
+        "[INFO]".accent(), pod_name.bold(), clone_name.bold(), ns.muted());
+    if let Some((kind, name)) = pod_owner(&pod) {
+        println!("{} The clone is detached from {} {}, which won't recreate or delete it", "[INFO]".warning(), kind, name);
+    }
+    println!("{} Labels are replaced by kdbg-clone-of={}, so Services and controllers ignore the clone", 
+        "[INFO]".warning(), pod_name);
+    println!("{}", "-".repeat(100));
+    
+    let manifest_text = serde_json::to_string_pretty(&manifest)?;
+    if is_dry_run() {
+        println!("{} Would apply:", "[DRY RUN]".warning());
+        println!("{}", manifest_text);
+        return Ok(());
+    }
+    if !yes && !is_server_dry_run() && !confirm(&format!("Create pod {}?", clone_name))? {
+        anyhow::bail!("Aborted");
+    }
+    
+    let mut args = vec!["apply", "-n", &ns, "-f", "-"];
+    args.extend(server_dry_run_arg());
+    args.extend(extra_kubectl_args());
+    let stdout = if is_server_dry_run() { Stdio::inherit() } else { Stdio::null() };
+    let mut child = kubectl(&args)
+        .stdin(Stdio::piped())
+        .stdout(stdout)
+        .spawn()?;
+    child.stdin.take().unwrap().write_all(manifest_text.as_bytes())?;
+    
+    if !child.wait()?.success() {
+        anyhow::bail!("Failed to create pod {}", clone_name);
+    }
+    if reported_server_dry_run() {
+        return Ok(());
+    }
+    
+    println!("{} Created pod {}", "[SUCCESS]".success(), clone_name.bold());
+    println!("{} Delete it when done: kdbg delete {} -n {}", "[INFO]".accent(), clone_name, ns);
+    
+    Ok(())
+}
+
+// The pod as a fresh manifest: server-set metadata, status and scheduling
+// results go, so the API server accepts it as a new object
+fn standalone_pod(pod: &Value, name: &str) -> Value {
+    let mut pod = pod.clone();
+    let original = pod["metadata"]["name"].as_str().unwrap_or("").to_string();
+    
+    if let Some(obj) = pod.as_object_mut() {
+        obj.remove("status");
+        obj.insert("apiVersion".to_string(), Value::from("v1"));
+        obj.insert("kind".to_string(), Value::from("Pod"));
+    }
+    if let Some(meta) = pod["metadata"].as_object_mut() {
+        for key in [
+            "uid", "resourceVersion", "generation", "creationTimestamp", "deletionTimestamp",
+            "deletionGracePeriodSeconds", "selfLink", "managedFields", "ownerReferences", "generateName",
+        ] {
+            meta.remove(key);
+        }
+        meta.insert("name".to_string(), Value::from(name));
+        // Label values are capped at 63 characters, pod names aren't
+        let origin = original.get(..63).unwrap_or(&original).trim_end_matches(['-', '.']);
+        meta.insert("labels".to_string(), serde_json::json!({ "kdbg-clone-of": origin }));
+    }
+    // Without nodeName the scheduler places the clone anew; ephemeral containers
+    // can't be part of a create; a StatefulSet pod's hostname is its old name
+    if let Some(spec) = pod["spec"].as_object_mut() {
+        for key in ["nodeName", "ephemeralContainers", "hostname"] {
+            spec.remove(key);
+        }
+    }
+    
+    pod
+}
+
+// The copy runs sleep instead of the container's command, so pods that crash
+// on startup stay up long enough to poke around with the same spec and mounts
+fn debug_copy(pod_pattern: &str, namespace: &str, container: Option<&str>, keep: bool) -> Result<()> {
+    use std::time::{SystemTime, UNIX_EPOCH};
+    
+    let pod = resolve_pod(pod_pattern, Some(namespace.to_string()))?;
+    let (pod_name, ns) = pod_name_ns(&pod);
+    let containers = pod_containers(&pod);
+    
+    let container = match container {
+        Some(name) if !containers.iter().any(|c| c == name) => {
+            anyhow::bail!("Pod {} has no container '{}' (containers: {})", pod_name, name, containers.join(", "));
+        }
+        Some(name) => name.to_string(),
+        None => containers.first().cloned()
+            .ok_or_else(|| anyhow::anyhow!("Pod {} has no containers", pod_name))?,
+    };
+    
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs();
+    let copy_name = format!("{}-debug-{}", pod_name, timestamp);
+    
+    println!("{} Copying pod: {} -> {} (container: {}, namespace: {})", 
+        "[INFO]".accent(), pod_name.bold(), copy_name.bold(), container.warning(), ns.muted());
+    if keep {
+        println!("{} Copy will be kept after you exit the shell", "[INFO]".warning());
+    } else {
+        println!("{} Copy will be deleted when you exit the shell", "[INFO]".warning());
+    }
+    println!("{}", "-".repeat(100));
+    
+    let copy_to = format!("--copy-to={}", copy_name);
+    let container_arg = format!("--container={}", container);
+    let status = kubectl(&["debug", &pod_name, "-n", &ns, &copy_to, &container_arg, "--", "sleep", "infinity"]).status()?;
+    if !status.success() {
+        anyhow::bail!("Failed to create a copy of pod {}", pod_name);
+    }
+    
+    // Readiness probes may still target the real process, so wait for Running rather than Ready
+    let pod_ref = format!("pod/{}", copy_name);
+    let mut status = kubectl(&["wait", &pod_ref, "-n", &ns, "--for=jsonpath={.status.phase}=Running", "--timeout=120s"]).status()?;
+    if status.success() {
+        status = kubectl(&["exec", "-it", &copy_name, "-n", &ns, "-c", &container, "--", "/bin/sh"])
+            .stdin(Stdio::inherit())
+            .stdout(Stdio::inherit())
+            .stderr(Stdio::inherit())
+            .status()?;
+    } else {
+        println!("{} Copy {} didn't start", "[ERROR]".danger(), copy_name.bold());
+    }
+    
+    println!("{}", "-".repeat(100));
+    if keep {
+        println!("{} Copy {} is still running", "[INFO]".accent(), copy_name.bold());
+        println!("  Re-enter: kubectl exec -it {} -n {} -c {} -- /bin/sh", copy_name, ns, container);
+        println!("  Delete:   kubectl delete pod {} -n {}", copy_name, ns);
+    } else {
+        println!("{} Deleting copy {}", "[INFO]".accent(), copy_name.bold());
+        let deleted = kubectl(&["delete", "pod", &copy_name, "-n", &ns, "--wait=false"]).status()?;
+        if !deleted.success() {
+            println!("{} Couldn't delete {}; remove it with: kubectl delete pod {} -n {}", 
+                "[WARN]".warning(), copy_name, copy_name, ns);
+        }
+    }
+    
+    if !status.success() {
+        anyhow::bail!("Shell exited with an error");
+    }
+    
+    Ok(())
+}
+
+fn debug_ephemeral(pod_pattern: &str, image: &str, namespace: &str, profile: Option<&str>, pull_policy: Option<&str>) -> Result<()> {
+    let (pod_name, ns) = find_pod(pod_pattern, Some(namespace.to_string()))?;
+    
+    println!("{} Attaching debug container to pod: {} (image: {}, namespace: {})", 
+        "[INFO]".accent(), pod_name.bold(), image.warning(), ns.muted());
+    if let Some(profile) = profile {
+        println!("{} Profile: {}", "[INFO]".accent(), profile.warning());
+    }
+    println!("{}", "-".repeat(100));
+    
+    let mut args = vec!["debug", "-it", &pod_name, "-n", &ns, "--image", image];
+    
+    if let Some(profile) = profile {
+        args.extend(&["--profile", profile]);
+    }
+    
+    if let Some(policy) = pull_policy {
+        args.extend(&["--image-pull-policy", policy]);
+    }
+    
+    let status = kubectl(&args)
+        .stdin(Stdio::inherit())
+        .stdout(Stdio::inherit())
+        .stderr(Stdio::inherit())
+        .status()?;
+    
+    if !status.success() {
+        anyhow::bail!("Failed to attach debug container");
+    }
+    
+    Ok(())
+}
+
+fn check_debug_profile(profile: &str) -> Result<()> {
+    let Some((_, min_minor)) = DEBUG_PROFILES.iter().find(|(name, _)| *name == profile) else {
+        let known: Vec<_> = DEBUG_PROFILES.iter().map(|(name, _)| *name).collect();
+        anyhow::bail!("Unknown debug profile '{}' (expected one of: {})", profile, known.join(", "));
+    };
+    
+    let minor = kubectl_minor_version()?;
+    if minor < *min_minor {
+        anyhow::bail!("Debug profile '{}' requires kubectl 1.{}+ (found 1.{})", profile, min_minor, minor);
+    }
+    
+    Ok(())
+}
+
+// Oldest kubectl whose flags kdbg relies on (e.g. `debug`, `-o jsonpath` waits)
+const MIN_KUBECTL_MINOR: u32 = 20;
+const PREFLIGHT_TTL_SECS: u64 = 24 * 60 * 60;
+
+// Makes sure kubectl is there before a command gets halfway. Runs on every
+// invocation, so the version comes from the discovery cache: the happy path
+// is a PATH lookup and one small file read
+fn preflight(command: &Commands) -> Result<()> {
+    if matches!(command, Commands::Completions { .. } | Commands::History { .. }
+        | Commands::CompleteNamespaces | Commands::CompleteContexts)
+    {
+        return Ok(());
+    }
+    
+    // Only a missing kubectl stops the command; an odd `version` output is
+    // left for the features that actually need the version to report
+    match kubectl_minor_version() {
+        Err(e) if find_kubectl().is_none() => Err(e),
+        _ => Ok(()),
+    }
+}
+
+fn kubectl_minor_version() -> Result<u32> {
+    let Some(path) = find_kubectl() else {
+        anyhow::bail!("kubectl not found on PATH; kdbg needs it to talk to the cluster (see https://kubernetes.io/docs/tasks/tools/)");
+    };
+    // An upgraded binary has a new mtime, which invalidates the cached version
+    let modified = std::fs::metadata(&path)
+        .and_then(|meta| meta.modified())
+        .ok()
+        .and_then(|time| time.duration_since(std::time::UNIX_EPOCH).ok())
+        .map_or(0, |age| age.as_secs());
+    
+    if let Some(minor) = cached_kubectl_minor(&path, modified) {
+        return Ok(minor);
+    }
+    
+    let output = kubectl(&["version", "--client", "-o", "json"]).output()?;
+    
+    if !output.status.success() {
+        anyhow::bail!("Failed to get kubectl version");
+    }
+    
+    let json: Value = serde_json::from_slice(&output.stdout)?;
+    
+    // Some distributions report the minor version as e.g. "27+"
+    let minor = json["clientVersion"]["minor"]
+        .as_str()
+        .map(|minor| minor.trim_end_matches('+'))
+        .and_then(|minor| minor.parse().ok())
+        .ok_or_else(|| anyhow::anyhow!("Could not parse kubectl version"))?;
+    
+    // Warned once per probe rather than on every run
+    if minor < MIN_KUBECTL_MINOR {
+        eprintln!("{} kubectl 1.{} is older than 1.{}; some kdbg commands may fail", 
+            "[WARN]".warning(), minor, MIN_KUBECTL_MINOR);
+    }
+    remember_kubectl(&path, modified, minor);
+    
+    Ok(minor)
+}
+
+fn find_kubectl() -> Option<PathBuf> {
+    let name = format!("kubectl{}", std::env::consts::EXE_SUFFIX);
+    std::env::split_paths(&std::env::var_os("PATH")?)
+        .map(|dir| dir.join(&name))
+        .find(|path| path.is_file())
+}
+
+fn discovery_cache_path() -> Option<PathBuf> {
+    state_dir().map(|dir| dir.join("kubectl.json"))
+}
+
+fn cached_kubectl_minor(path: &std::path::Path, modified: u64) -> Option<u32> {
+    let text = std::fs::read_to_string(discovery_cache_path()?).ok()?;
+    let cache: Value = serde_json::from_str(&text).ok()?;
+    let age = chrono::Utc::now().timestamp() - cache["checked_at"].as_i64()?;
+    
+    let fresh = cache["path"].as_str() == path.to_str()
+        && cache["modified"].as_u64() == Some(modified)
+        && (0..PREFLIGHT_TTL_SECS as i64).contains(&age);
+    if !fresh {
+        return None;
+    }
+    cache["minor"].as_u64().map(|minor| minor as u32)
+}
+
+// Best effort like the shell cache: failing to write just means probing again
+fn remember_kubectl(path: &std::path::Path, modified: u64, minor: u32) {
+    let Some(cache_path) = discovery_cache_path() else {
+        return;
+    };
+    let cache = serde_json::json!({
+        "path": path.to_string_lossy(),
+        "modified": modified,
+        "minor": minor,
+        "checked_at": chrono::Utc::now().timestamp(),
+    });
+    
+    if let Some(dir) = cache_path.parent() {
+        let _ = std::fs::create_dir_all(dir);
+    }
+    let _ = std::fs::write(&cache_path, cache.to_string());
+}
+
+// With `recovery`, stays until the controller's replacement is ready (or the
+// timeout), printing the Warning events that explain a slow start
+fn restart_pod(
+    pod_pattern: &str,
+    namespace: Option<String>,
+    rollout: bool,
+    confirm_all_namespaces: bool,
+    yes: bool,
+    options: &DeleteOptions,
+    recovery: Option<std::time::Duration>,
+) -> Result<()> {
+    guard_all_namespaces(&namespace, confirm_all_namespaces, "restart")?;
+    let pod = resolve_pod(pod_pattern, namespace)?;
+    let (pod_name, ns) = pod_name_ns(&pod);
+    load_replicaset_owners(Some(&ns));
+    let owner = pod_owner(&pod);
+    
+    if rollout {
+        let (kind, name) = match owner {
+            Some((kind, name)) if matches!(kind.as_str(), "Deployment" | "StatefulSet" | "DaemonSet") => (kind, name),
+            _ => anyhow::bail!("Pod {} isn't managed by a Deployment, StatefulSet or DaemonSet", pod_name),
+        };
+        return rollout_restart(&kind, &name, &ns, yes);
+    }
+    
+    println!("{} Restarting pod: {} (namespace: {})", 
+        "[INFO]".accent(), pod_name.bold(), ns.muted());
+    let mut known_uids = Vec::new();
+    match &owner {
+        Some((kind, name)) => {
+            let siblings = controller_pods(kind, name, &ns)?;
+            known_uids.extend(siblings.iter().filter_map(|p| p["metadata"]["uid"].as_str().map(str::to_string)));
+            let others: Vec<&Value> = siblings.iter().filter(|p| pod_name_ns(p).0 != pod_name).collect();
+            println!("{} This will delete the pod; {} {} recreates it", "[INFO]".warning(), kind, name.bold());
+            match others.iter().filter(|p| is_pod_ready(p)).count() {
+                0 => println!("{} No other ready pod of {}: expect downtime until the new one is ready", "[WARN]".warning(), name),
+                ready => println!("{} {} other pods of {} keep serving meanwhile ({} ready)", 
+                    "[INFO]".accent(), others.len(), name, ready),
+            }
+        }
+        None => {
+            println!("{} Pod {} has no controller: it will be deleted and NOT recreated", "[WARN]".warning(), pod_name.bold());
+        }
+    }
+    
+    if !yes && !is_dry_run() && !is_server_dry_run() && !confirm(&format!("Restart pod {}?", pod_name))? {
+        anyhow::bail!("Aborted");
+    }
+    println!("{}", "-".repeat(100));
+    
+    if !delete_pod(&pod_name, &ns, options)? {
+        anyhow::bail!("Failed to delete pod");
+    }
+    
+    if is_dry_run() || reported_server_dry_run() {
+        return Ok(());
+    }
+    let gone = if options.wait { " and gone" } else { "" };
+    println!("{} Pod deleted{}. Waiting for recreation...", "[SUCCESS]".success(), gone);
+    
+    if let (Some(timeout), Some(owner)) = (recovery, &owner) {
+        known_uids.extend(pod["metadata"]["uid"].as_str().map(str::to_string));
+        let watched = (owner.clone(), ns.clone(), known_uids.clone());
+        let _events = EventWatch::start(&ns, move || {
+            let (owner, ns, known_uids) = &watched;
+            replacement_pods(owner, ns, known_uids)
+        });
+        wait_for_recovery(owner, &ns, &known_uids, timeout)?;
+    }
+    
+    Ok(())
+}
+
+// Polls for a pod of `owner` that didn't exist before the restart, printing
+// its phase as it changes, until it is ready
+fn wait_for_recovery(owner: &(String, String), ns: &str, known_uids: &[String], timeout: std::time::Duration) -> Result<()> {
+    let deadline = std::time::Instant::now() + timeout;
+    let mut last_state = String::new();
+    
+    loop {
+        std::thread::sleep(std::time::Duration::from_secs(2));
+        
+        let pods = replacement_pods(owner, ns, known_uids)?;
+        let replacement = pods.iter()
+            .filter(|pod| pod["metadata"]["deletionTimestamp"].is_null())
+            .max_by_key(|pod| pod["metadata"]["creationTimestamp"].as_str().unwrap_or(""));
+        
+        if let Some(pod) = replacement {
+            let (name, _) = pod_name_ns(pod);
+            if is_pod_ready(pod) {
+                println!("{} Replacement {} is ready", "[SUCCESS]".success(), name.bold());
+                return Ok(());
+            }
+            let phase = pod_phase(pod);
+            let state = match pod_reason(pod) {
+                Some(reason) => format!("{} ({})", phase, reason),
+                None => phase.to_string(),
+            };
+            if state != last_state {
+                println!("{} {}: {}", "[INFO]".accent(), name.bold(), phase_colored(phase, &state));
+                last_state = state;
+            }
+        }
+        
+        if std::time::Instant::now() >= deadline {
+            match replacement {
+                Some(pod) => anyhow::bail!("Timed out after {}s: {} isn't ready ({})", 
+                    timeout.as_secs(), pod_name_ns(pod).0, last_state),
+                None => anyhow::bail!("Timed out after {}s: {} {} created no replacement", 
+                    timeout.as_secs(), owner.0, owner.1),
+            }
+        }
+    }
+}
+
+// The pods of `owner` that weren't there before the restart
+fn replacement_pods(owner: &(String, String), ns: &str, known_uids: &[String]) -> Result<Vec<Value>> {
+    let pods = fetch_pods(&Some(ns.to_string()), None)?;
+    Ok(pods.into_iter()
+        .filter(|pod| pod_owner(pod).as_ref() == Some(owner))
+        .filter(|pod| !known_uids.iter().any(|uid| pod["metadata"]["uid"] == uid.as_str()))
+        .collect())
+}
+
+// Polls a namespace's Warning events in the background and prints the new ones
+// for the pods `pods` returns, asked again on every poll so pods created
+// meanwhile are covered; the output lands between the caller's own progress
+// lines. Stops when dropped
+struct EventWatch {
+    stop: std::sync::Arc<std::sync::atomic::AtomicBool>,
+}
+
+impl EventWatch {
+    fn start(ns: &str, pods: impl Fn() -> Result<Vec<Value>> + Send + 'static) -> Self {
+        use std::sync::Arc;
+        use std::sync::atomic::{AtomicBool, Ordering};
+        
+        let stop = Arc::new(AtomicBool::new(false));
+        let ns = ns.to_string();
+        let started = chrono::Utc::now().timestamp();
+        
+        let stopped = Arc::clone(&stop);
+        std::thread::spawn(move || {
+            // An event seen again with a higher count happened again
+            let mut seen: BTreeMap<String, u64> = BTreeMap::new();
+            let mut failed = false;
+            while !stopped.load(Ordering::Relaxed) {
+                std::thread::sleep(std::time::Duration::from_secs(EVENT_WATCH_INTERVAL_SECS));
+                
+                let polled = pods().and_then(|pods| {
+                    fetch_events(&["-n", &ns, "--field-selector", "type=Warning"]).map(|events| (pods, events))
+                });
+                let (pods, events) = match polled {
+                    Ok(polled) => polled,
+                    Err(e) => {
+                        if !failed {
+                            eprintln!("{} Can't watch events: {}", "[WARN]".warning(), e);
+                            failed = true;
+                        }
+                        continue;
+                    }
+                };
+                let mut fresh: Vec<&Value> = events.iter()
+                    .filter(|e| e["involvedObject"]["kind"] == "Pod")
+                    .filter(|e| pods.iter().any(|pod| is_event_for(e, pod)))
+                    .filter(|e| event_timestamp(e, "lastTimestamp") >= started)
+                    .filter(|e| {
+                        let uid = e["metadata"]["uid"].as_str().unwrap_or("").to_string();
+                        seen.insert(uid, event_count(e)) != Some(event_count(e))
+                    })
+                    .collect();
+                fresh.sort_by_key(|e| event_timestamp(e, "lastTimestamp"));
+                
+                if stopped.load(Ordering::Relaxed) {
+                    break;
+                }
+                for event in fresh {
+                    print_watched_event(event);
+                }
+            }
+        });
+        
+        EventWatch { stop }
+    }
+}
+
+// By uid where the event has one, so a StatefulSet pod's predecessor
+// (same name) doesn't count
+fn is_event_for(event: &Value, pod: &Value) -> bool {
+    let object = &event["involvedObject"];
+    match object["uid"].as_str() {
+        Some(uid) if !uid.is_empty() => pod["metadata"]["uid"] == uid,
+        _ => object["name"] == pod["metadata"]["name"],
+    }
+}
+
+impl Drop for EventWatch {
+    fn drop(&mut self) {
+        self.stop.store(true, std::sync::atomic::Ordering::Relaxed);
+    }
+}
+
+fn print_watched_event(event: &Value) {
+    let time = chrono::DateTime::from_timestamp(event_timestamp(event, "lastTimestamp"), 0)
+        .map(|dt| dt.with_timezone(&chrono::Local).format("%H:%M:%S").to_string())
+        .unwrap_or_default();
+    let repeats = match event_count(event) {
+        1 => String::new(),
+        n => format!(" (x{})", n),
+    };
+    
+    println!("{} {} pod/{} {}: {}{}", 
+        "[WARN]".warning(), time.muted(), event["involvedObject"]["name"].as_str().unwrap_or(""),
+        event["reason"].as_str().unwrap_or("").bold(), event["message"].as_str().unwrap_or("").trim(), repeats);
+}
+
+fn delete_single_pod(pod_pattern: &str, namespace: Option<String>, confirm_all_namespaces: bool, options: &DeleteOptions) -> Result<()> {
+    guard_all_namespaces(&namespace, confirm_all_namespaces, "delete")?;
+    let (pod_name, ns) = find_pod(pod_pattern, namespace)?;
+    
+    println!("{} Deleting pod: {} (namespace: {})", 
+        "[INFO]".accent(), pod_name.bold(), ns.muted());
+    println!("{}", "-".repeat(100));
+    
+    if !delete_pod(&pod_name, &ns, options)? {
+        anyhow::bail!("Failed to delete pod");
+    }
+    
+    if !is_dry_run() && !reported_server_dry_run() {
+        let gone = if options.wait { " and gone" } else { "" };
+        println!("{} Pod {} deleted{}", "[SUCCESS]".success(), pod_name, gone);
+    }
+    
+    Ok(())
+}
+
+// Shared by restart -l and delete -l; `verb` is only used in messages
+fn delete_selector(
+    selector: &str,
+    namespace: Option<String>,
+    confirm_all_namespaces: bool,
+    yes: bool,
+    options: &DeleteOptions,
+    verb: &str,
+) -> Result<()> {
+    guard_all_namespaces(&namespace, confirm_all_namespaces, verb)?;
+    let pods = resolve_selector(selector, &namespace)?;
+    
+    let question = match verb {
+        "restart" => format!("Delete these {} pods and let their controllers recreate them?", pods.len()),
+        _ => format!("Delete these {} pods?", pods.len()),
+    };
+    if !yes && !is_dry_run() && !is_server_dry_run() && !confirm(&question)? {
+        anyhow::bail!("Aborted");
+    }
+    println!("{}", "-".repeat(100));
+    
+    let mut results = OpResults::default();
+    for pod in &pods {
+        if is_dry_run() {
+            delete_pod(&pod.name, &pod.namespace, options)?;
+            results.record_dry_run(pod);
+            continue;
+        }
+        let _ = results.record(pod, delete_pod_status(&pod.name, &pod.namespace, options).map(|s| (s, ())));
+    }
+    
+    results.finish(verb)?;
+    if verb == "restart" && !is_dry_run() && !reported_server_dry_run() {
+        println!("{} Waiting for their controllers to recreate them...", "[INFO]".accent());
+    }
+    
+    Ok(())
+}
+
+// Under --dry-run only prints the command. With --wait, kubectl returns once
+// the pod has left the API server, so a follow-up command can't find it half-deleted.
+fn delete_pod(name: &str, ns: &str, options: &DeleteOptions) -> Result<bool> {
+    if is_dry_run() {
+        println!("{} Would run: kubectl {}", "[DRY RUN]".warning(), delete_pod_args(name, ns, options).join(" "));
+        return Ok(true);
+    }
+    
+    Ok(delete_pod_status(name, ns, options)?.success())
+}
+
+// delete_pod without the dry-run check, for callers that record the exit status
+fn delete_pod_status(name: &str, ns: &str, options: &DeleteOptions) -> std::io::Result<std::process::ExitStatus> {
+    let args = delete_pod_args(name, ns, options);
+    kubectl(&args.iter().map(String::as_str).collect::<Vec<_>>()).status()
+}
+
+fn delete_pod_args(name: &str, ns: &str, options: &DeleteOptions) -> Vec<String> {
+    let mut args: Vec<String> = ["delete", "pod", name, "-n", ns].map(String::from).into();
+    args.extend(options.grace_period.map(|secs| format!("--grace-period={}", secs)));
+    if options.wait {
+        args.push("--wait=true".to_string());
+    }
+    args.extend(server_dry_run_arg().map(String::from));
+    args.extend(extra_kubectl_args().map(String::from));
+    args
+}
+
+// Destructive verbs must not quietly search every namespace: a loose pattern
+// could pick a pod from a namespace the user never meant to touch
+fn guard_all_namespaces(namespace: &Option<String>, confirmed: bool, verb: &str) -> Result<()> {
+    if namespace.is_none() && !confirmed {
+        anyhow::bail!("Refusing to {} across all namespaces; pass -n NAMESPACE (or --confirm-all-namespaces)", verb);
+    }
+    
+    Ok(())
+}
+
+fn rollout_restart(kind: &str, name: &str, ns: &str, yes: bool) -> Result<()> {
+    let target = format!("{}/{}", kind.to_lowercase(), name);
+    let target = target.as_str();
+    println!("{} Rollout restart: {} (namespace: {})", 
+        "[INFO]".accent(), target.bold(), ns.muted());
+    
+    let pods = controller_pods(kind, name, ns)?;
+    println!("{} Every pod of {} is replaced, a few at a time per its rollout strategy:", "[INFO]".warning(), target);
+    print_pod_impact(&pods);
+    println!("{}", "-".repeat(100));
+    
+    if is_dry_run() {
+        return preview_rollout_restart(target, ns);
+    }
+    if !yes && !is_server_dry_run() && !confirm(&format!("Restart all {} pods of {}?", pods.len(), target))? {
+        anyhow::bail!("Aborted");
+    }
+    
+    let mut args = vec!["rollout", "restart", target, "-n", ns];
+    args.extend(server_dry_run_arg());
+    args.extend(extra_kubectl_args());
+    let status = kubectl(&args).status()?;
+    
+    if !status.success() {
+        anyhow::bail!("Failed to restart {}", target);
+    }
+    if reported_server_dry_run() {
+        return Ok(());
+    }
+    
+    println!("{} Rollout started. Watch it with: kubectl rollout status {} -n {}", 
+        "[SUCCESS]".success(), target, ns);
+    
+    Ok(())
+}
+
+// Pods a controller manages (ReplicaSets resolved to their Deployment)
+fn controller_pods(kind: &str, name: &str, ns: &str) -> Result<Vec<Value>> {
+    load_replicaset_owners(Some(ns));
+    let filter = format!("{}/{}", kind, name);
+    
+    Ok(fetch_pods(&Some(ns.to_string()), None)?
+        .into_iter()
+        .filter(|pod| owner_matches(pod, &filter))
+        .collect())
+}
+
+// One line per affected pod, so the blast radius is visible before confirming
+fn print_pod_impact(pods: &[Value]) {
+    for pod in pods.iter().take(SELECTOR_PREVIEW_LIMIT) {
+        let (name, _) = pod_name_ns(pod);
+        let phase = pod_phase(pod);
+        let ready = if is_pod_ready(pod) { "ready".success() } else { "not ready".warning() };
+        println!("  - {} {} {}", name.accent(), phase_colored(phase, phase), ready);
+    }
+    if pods.len() > SELECTOR_PREVIEW_LIMIT {
+        println!("  ... and {} more", pods.len() - SELECTOR_PREVIEW_LIMIT);
+    }
+}
+
+// TARGET is a pod (scaling its controller) or KIND/NAME, which still works
+// once the controller is at 0 and has no pods left to match
+fn scale(target: &str, replicas: u32, namespace: Option<String>, yes: bool) -> Result<()> {
+    let (kind, name, ns) = match target.split_once('/') {
+        Some((kind, name)) => {
+            single_namespace(&namespace)?;
+            let Some(ns) = namespace else {
+                anyhow::bail!("Scaling {} needs -n NAMESPACE", target);
+            };
+            (kind.to_string(), name.to_string(), ns)
+        }
+        None => {
+            let pod = resolve_pod(target, namespace)?;
+            let (pod_name, ns) = pod_name_ns(&pod);
+            load_replicaset_owners(Some(&ns));
+            match pod_owner(&pod) {
+                Some((kind, name)) if matches!(kind.as_str(), "Deployment" | "StatefulSet" | "ReplicaSet") => (kind, name, ns),
+                _ => anyhow::bail!("Pod {} isn't managed by a Deployment, StatefulSet or ReplicaSet", pod_name),
+            }
+        }
+    };
+    let resource = format!("{}/{}", kind.to_lowercase(), name);
+    
+    let output = run_kubectl(&["get", &resource, "-n", &ns, "-o", "json"])?;
+    if !output.status.success() {
+        anyhow::bail!("Failed to get {}: {}", resource, String::from_utf8_lossy(&output.stderr).trim());
+    }
+    let controller: Value = serde_json::from_slice(&output.stdout)?;
+    let current = controller["spec"]["replicas"].as_u64().unwrap_or(1) as u32;
+    let kind = controller["kind"].as_str().unwrap_or(&kind).to_string();
+    
+    println!("{} Scaling {} (namespace: {}): {} -> {} replicas", 
+        "[INFO]".accent(), resource.bold(), ns.muted(), current, replicas);
+    
+    if replicas == current {
+        println!("{} Already at {} replicas, nothing to do", "[INFO]".accent(), replicas);
+        return Ok(());
+    }
+    
+    let pods = controller_pods(&kind, &name, &ns)?;
+    if replicas > current {
+        println!("{} This will start {} new pods next to the {} current ones", "[INFO]".accent(), replicas - current, pods.len());
+    } else if replicas == 0 {
+        let running = pods.iter().filter(|pod| pod_phase(pod) == "Running").count();
+        println!("{} This will terminate {} running pods:", "[WARN]".warning(), running);
+        print_pod_impact(&pods);
+    } else if kind == "StatefulSet" {
+        // StatefulSets remove the highest ordinals first
+        let doomed: Vec<Value> = pods.into_iter()
+            .filter(|pod| {
+                pod_name_ns(pod).0.rsplit_once('-')
+                    .and_then(|(_, ordinal)| ordinal.parse::<u32>().ok())
+                    .is_some_and(|ordinal| ordinal >= replicas)
+            })
+            .collect();
+        println!("{} This will terminate {} pods:", "[WARN]".warning(), doomed.len());
+        print_pod_impact(&doomed);
+    } else {
+        println!("{} This will terminate {} of these pods ({} picks which):", "[WARN]".warning(), current - replicas, kind);
+        print_pod_impact(&pods);
+    }
+    println!("{}", "-".repeat(100));
+    
+    let replicas_arg = format!("--replicas={}", replicas);
+    let mut args = vec!["scale", &resource, "-n", &ns, &replicas_arg];
+    args.extend(server_dry_run_arg());
+    args.extend(extra_kubectl_args());
+    
+    if is_dry_run() {
+        println!("{} Would run: kubectl {}", "[DRY RUN]".warning(), args.join(" "));
+        return Ok(());
+    }
+    if !yes && !is_server_dry_run() && !confirm(&format!("Scale {} to {} replicas?", resource, replicas))? {
+        anyhow::bail!("Aborted");
+    }
+    
+    let status = kubectl(&args).status()?;
+    if !status.success() {
+        anyhow::bail!("Failed to scale {}", resource);
+    }
+    if reported_server_dry_run() {
+        return Ok(());
+    }
+    
+    println!("{} {} scaled to {} replicas", "[SUCCESS]".success(), resource, replicas);
+    
+    Ok(())
+}
+
+// Diffs the live object against the server's dry-run of the restart, which
+// shows the new restartedAt template annotation (and so the new pod template)
+fn preview_rollout_restart(target: &str, ns: &str) -> Result<()> {
+    let current = run_kubectl(&["get", target, "-n", ns, "-o", "json"])?;
+    if !current.status.success() {
+        anyhow::bail!("Failed to get {}: {}", target, String::from_utf8_lossy(&current.stderr).trim());
+    }
+    
+    let restarted = run_kubectl(&["rollout", "restart", target, "-n", ns, "--dry-run=server", "-o", "json"])?;
+    if !restarted.status.success() {
+        anyhow::bail!("Dry run failed: {}", String::from_utf8_lossy(&restarted.stderr).trim());
+    }
+    
+    let before = normalize_object(serde_json::from_slice(&current.stdout)?);
+    let after = normalize_object(serde_json::from_slice(&restarted.stdout)?);
+    
+    println!("{} Nothing was changed; a restart would apply:", "[DRY RUN]".warning());
+    for line in unified_diff(&before, &after, "current", "restarted") {
+        println!("{}", colorize_diff_line(&line));
+    }
+    
+    Ok(())
+}
+
+// Drops server bookkeeping that changes on every write
+fn normalize_object(mut object: Value) -> String {
+    if let Some(obj) = object.as_object_mut() {
+        obj.remove("status");
+    }
+    
+    if let Some(meta) = object["metadata"].as_object_mut() {
+        for key in ["resourceVersion", "generation", "managedFields"] {
+            meta.remove(key);
+        }
+    }
+    
+    serde_json::to_string_pretty(&object).unwrap_or_default()
+}
+
+// Polls the pod and grabs `logs --previous` as soon as a container's restart
+// count goes up, before a second restart overwrites the evidence
+fn watch_pod(
+    pod_pattern: &str,
+    namespace: Option<String>,
+    interval: u64,
+    dir: &std::path::Path,
+    previous_events: bool,
+) -> Result<()> {
+    let pod = resolve_pod(pod_pattern, namespace)?;
+    let (pod_name, ns) = pod_name_ns(&pod);
+    std::fs::create_dir_all(dir)?;
+    
+    println!("{} Watching pod: {} (namespace: {}), saving crash logs to {}", 
+        "[INFO]".accent(), pod_name.bold(), ns.muted(), dir.display());
+    println!("{} Press Ctrl+C to stop", "[INFO]".accent());
+    println!("{}", "-".repeat(100));
+    
+    let mut seen = container_restart_counts(&pod);
+    
+    loop {
+        std::thread::sleep(std::time::Duration::from_secs(interval));
+        
+        let output = run_kubectl(&["get", "pod", &pod_name, "-n", &ns, "-o", "json"])?;
+        if !output.status.success() {
+            anyhow::bail!("Pod {} is gone: {}", pod_name, String::from_utf8_lossy(&output.stderr).trim());
+        }
+        let pod: Value = serde_json::from_slice(&output.stdout)?;
+        
+        for status in pod["status"]["containerStatuses"].as_array().into_iter().flatten() {
+            let container = status["name"].as_str().unwrap_or("unknown");
+            let count = status["restartCount"].as_u64().unwrap_or(0);
+            let previous = seen.insert(container.to_string(), count).unwrap_or(0);
+            
+            if count <= previous {
+                continue;
+            }
+            
+            println!("{} {}", "[CRASH]".danger().bold(), container_status_line(status));
+            match save_previous_logs(&pod_name, &ns, container, dir) {
+                Ok(path) => println!("{} Saved previous logs: {}", "[SUCCESS]".success(), path.display()),
+                Err(e) => eprintln!("{} {}", "[ERROR]".danger(), e),
+            }
+            
+            if previous_events {
+                match save_pod_events(&pod_name, &ns, container, dir) {
+                    Ok(path) => println!("{} Saved events: {}", "[SUCCESS]".success(), path.display()),
+                    Err(e) => eprintln!("{} {}", "[ERROR]".danger(), e),
+                }
+            }
+        }
+    }
+}
+
+fn container_restart_counts(pod: &Value) -> BTreeMap<String, u64> {
+    pod["status"]["containerStatuses"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|c| Some((c["name"].as_str()?.to_string(), c["restartCount"].as_u64().unwrap_or(0))))
+        .collect()
+}
+
+fn save_previous_logs(pod_name: &str, ns: &str, container: &str, dir: &std::path::Path) -> Result<PathBuf> {
+    let output = run_kubectl(&["logs", pod_name, "-n", ns, "-c", container, "--previous"])?;
+    
+    if !output.status.success() {
+        anyhow::bail!("Failed to get previous logs of {}: {}", container, String::from_utf8_lossy(&output.stderr).trim());
+    }
+    
+    let stamp = chrono::Local::now().format("%Y%m%d-%H%M%S");
+    let path = dir.join(format!("{}-{}-{}.log", pod_name, container, stamp));
+    std::fs::write(&path, &output.stdout)?;
+    
+    Ok(path)
+}
+
+// Events expire after an hour by default; keep the OOMKilled / probe failure
+// reasons next to the logs of the crash they explain
+fn save_pod_events(pod_name: &str, ns: &str, container: &str, dir: &std::path::Path) -> Result<PathBuf> {
+    let selector = format!("involvedObject.name={}", pod_name);
+    let mut events = fetch_events(&["-n", ns, "--field-selector", &selector])?;
+    events.sort_by_key(|e| event_timestamp(e, "lastTimestamp"));
+    
+    let stamp = chrono::Local::now().format("%Y%m%d-%H%M%S");
+    let path = dir.join(format!("{}-{}-{}.events.json", pod_name, container, stamp));
+    std::fs::write(&path, serde_json::to_string_pretty(&events)?)?;
+    
+    Ok(path)
+}
+
+fn wait_pods(
+    pod_pattern: Option<&str>,
+    selector: Option<&str>,
+    namespace: Option<String>,
+    condition: &str,
+    timeout: &str,
+    show_events: bool,
+) -> Result<()> {
+    let for_arg = format!("--for={}", condition);
+    let timeout_arg = format!("--timeout={}", timeout);
+    
+    let (target, ns, pod) = match (pod_pattern, selector) {
+        (_, Some(sel)) => {
+            single_namespace(&namespace)?;
+            // Events are namespaced, so the watch needs one namespace to poll
+            if show_events && namespace.is_none() {
+                anyhow::bail!("--show-events needs a namespace (-n)");
+            }
+            resolve_selector(sel, &namespace)?;
+            (format!("pods matching {}", sel), namespace, None)
+        }
+        (Some(pattern), None) => {
+            let pod = resolve_pod(pattern, namespace)?;
+            let (pod_name, ns) = pod_name_ns(&pod);
+            (pod_name, Some(ns), Some(pod))
+        }
+        (None, None) => anyhow::bail!("Specify a pod or --selector"),
+    };
+    
+    println!("{} Waiting for {} ({}, timeout {})", 
+        "[INFO]".accent(), target.bold(), condition, timeout);
+    println!("{}", "-".repeat(100));
+    
+    let pod_ref = format!("pod/{}", target);
+    let mut args = match selector {
+        Some(sel) => vec!["wait", "pods", "-l", sel],
+        None => vec!["wait", &pod_ref],
+    };
+    
+    match &ns {
+        Some(ns) => args.extend(&["-n", ns]),
+        None => args.push("--all-namespaces"),
+    }
+    args.extend(&[for_arg.as_str(), timeout_arg.as_str()]);
+    args.extend(extra_kubectl_args());
+    
+    // Pods the selector gains during the wait are picked up on the next poll
+    let _events = match (&ns, show_events, pod) {
+        (Some(ns), true, Some(pod)) => Some(EventWatch::start(ns, move || Ok(vec![pod.clone()]))),
+        (Some(ns), true, None) => {
+            let (namespace, selector) = (Some(ns.clone()), selector.unwrap_or_default().to_string());
+            Some(EventWatch::start(ns, move || fetch_pods(&namespace, Some(&selector))))
+        }
+        _ => None,
+    };
+    let status = kubectl(&args).status()?;
+    
+    if !status.success() {
+        anyhow::bail!("Timed out or failed waiting for {}", condition);
+    }
+    
+    println!("{} {} satisfied {}", "[SUCCESS]".success(), target, condition);
+    
+    Ok(())
+}
+
+fn show_events(pod_pattern: &str, namespace: Option<String>, sort_by: EventSort, reverse: bool) -> Result<()> {
+    let (pod_name, ns) = find_pod(pod_pattern, namespace)?;
+    validate_resource_name(&pod_name)?;
+    
+    println!("{} Events for pod: {} (namespace: {})", 
+        "[INFO]".accent(), pod_name.bold(), ns.muted());
+    println!("{}", "-".repeat(100));
+    
+    let selector = format!("involvedObject.name={}", pod_name);
+    let events = fetch_events(&["-n", &ns, "--field-selector", &selector])?;
+    print_events(events, sort_by, reverse, false);
+    
+    Ok(())
+}
+
+fn show_events_all_namespaces(pod_pattern: Option<&str>, sort_by: EventSort, reverse: bool) -> Result<()> {
+    let mut args = vec!["--all-namespaces"];
+    
+    let selector;
+    if let Some(pattern) = pod_pattern {
+        let (pod_name, _) = find_pod(pattern, None)?;
+        validate_resource_name(&pod_name)?;
+        
+        println!("{} Events for pod: {} (all namespaces)", "[INFO]".accent(), pod_name.bold());
+        selector = format!("involvedObject.name={}", pod_name);
+        args.extend(&["--field-selector", &selector]);
+    } else {
+        println!("{} Events in all namespaces", "[INFO]".accent());
+    }
+    println!("{}", "-".repeat(100));
+    
+    let events = fetch_events(&args)?;
+    print_events(events, sort_by, reverse, true);
+    
+    Ok(())
+}
+
+fn fetch_events(scope: &[&str]) -> Result<Vec<Value>> {
+    let mut args = vec!["get", "events", "-o", "json"];
+    args.extend(scope);
+    
+    let output = run_kubectl(&args)?;
+    
+    if !output.status.success() {
+        anyhow::bail!("Failed to get events: {}", String::from_utf8_lossy(&output.stderr).trim());
+    }
+    
+    let json: Value = serde_json::from_slice(&output.stdout)?;
+    Ok(json["items"].as_array().cloned().unwrap_or_default())
+}
+
+// Sorted client-side: kubectl's --sort-by has no descending order
+fn print_events(mut events: Vec<Value>, sort_by: EventSort, reverse: bool, show_namespace: bool) {
+    if events.is_empty() {
+        println!("No events found");
+        return;
+    }
+    
+    match sort_by {
+        EventSort::LastTimestamp => events.sort_by_key(|e| event_timestamp(e, "lastTimestamp")),
+        EventSort::FirstTimestamp => events.sort_by_key(|e| event_timestamp(e, "firstTimestamp")),
+        EventSort::Count => events.sort_by_key(event_count),
+        EventSort::Type => events.sort_by(|a, b| a["type"].as_str().cmp(&b["type"].as_str())),
+    }
+    if reverse {
+        events.reverse();
+    }
+    
+    let ns_header = if show_namespace { format!("{:<15} ", "NAMESPACE") } else { String::new() };
+    println!("{}{:<10} {:<8} {:<20} {:<6} {:<30} MESSAGE", 
+        ns_header, "LAST SEEN", "TYPE", "REASON", "COUNT", "OBJECT");
+    
+    for event in &events {
+        let ns_column = if show_namespace {
+            format!("{} ", pad(event["metadata"]["namespace"].as_str().unwrap_or(""), 15))
+        } else {
+            String::new()
+        };
+        
+        let last_seen = match event_timestamp(event, "lastTimestamp") {
+            0 => "<unknown>".to_string(),
+            ts => chrono::DateTime::from_timestamp(ts, 0)
+                .map(|dt| calculate_age(&dt.to_rfc3339()))
+                .unwrap_or_default(),
+        };
+        
+        let kind = event["type"].as_str().unwrap_or("");
+        let type_colored = match kind {
+            "Warning" => format!("{:<8}", kind).warning(),
+            _ => format!("{:<8}", kind).normal(),
+        };
+        
+        let object = format!("{}/{}", 
+            event["involvedObject"]["kind"].as_str().unwrap_or("").to_lowercase(),
+            event["involvedObject"]["name"].as_str().unwrap_or(""));
+        
+        println!("{}{:<10} {} {:<20} {:<6} {} {}", 
+            namespace_colored(event["metadata"]["namespace"].as_str().unwrap_or(""), &ns_column), last_seen, type_colored,
+            event["reason"].as_str().unwrap_or(""), event_count(event), pad(&object, 30),
+            event["message"].as_str().unwrap_or("").trim());
+    }
+}
+
+// Newer events (events.k8s.io) leave the legacy timestamps empty and set eventTime
+fn event_timestamp(event: &Value, field: &str) -> i64 {
+    [&event[field], &event["eventTime"], &event["metadata"]["creationTimestamp"]]
+        .into_iter()
+        .find_map(|v| v.as_str())
+        .and_then(|ts| chrono::DateTime::parse_from_rfc3339(ts).ok())
+        .map_or(0, |dt| dt.timestamp())
+}
+
+fn event_count(event: &Value) -> u64 {
+    event["count"].as_u64()
+        .or_else(|| event["series"]["count"].as_u64())
+        .unwrap_or(1)
+}
+
+fn copy_files(src: &str, dest: &str, namespace: Option<String>, container: Option<&str>) -> Result<()> {
+    let (pod_pattern, remote_path, upload) = match (split_pod_path(src), split_pod_path(dest)) {
+        (Some((pod, path)), None) => (pod, path, false),
+        (None, Some((pod, path))) => (pod, path, true),
+        _ => anyhow::bail!("Exactly one of source and destination must be POD:PATH"),
+    };
+    
+    let pod = resolve_pod(pod_pattern, namespace)?;
+    let (pod_name, ns) = pod_name_ns(&pod);
+    let containers = pod_containers(&pod);
+    
+    let container = match container {
+        Some(c) if !containers.iter().any(|name| name == c) => {
+            anyhow::bail!("Container '{}' not found in pod {} (containers: {})", 
+                c, pod_name, containers.join(", "));
+        }
+        Some(c) => c.to_string(),
+        None if containers.len() == 1 => containers[0].clone(),
+        None => {
+            println!("{} Pod {} has multiple containers:", "[INFO]".warning(), pod_name.accent());
+            for name in &containers {
+                println!("  - {}", name.accent());
+            }
+            anyhow::bail!("Please choose one with --container");
+        }
+    };
+    
+    let remote = format!("{}/{}:{}", ns, pod_name, remote_path);
+    let (from, to) = if upload { (src, remote.as_str()) } else { (remote.as_str(), dest) };
+    
+    println!("{} Copying {} -> {} (container: {})", 
+        "[INFO]".accent(), from.bold(), to.bold(), container.warning());
+    println!("{}", "-".repeat(100));
+    
+    let mut args = vec!["cp", from, to, "-c", &container];
+    args.extend(extra_kubectl_args());
+    let output = kubectl(&args)
+        .stdout(Stdio::inherit())
+        .output()?;
+    
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        if stderr.contains("\"tar\"") || stderr.contains("tar: not found") {
+            anyhow::bail!("kubectl cp needs tar inside the container, but the image for '{}' doesn't have it", container);
+        }
+        anyhow::bail!("Copy failed: {}", stderr.trim());
+    }
+    
+    println!("{} Copied {} -> {}", "[SUCCESS]".success(), from, to);
+    
+    Ok(())
+}
+
+// Splits "pod:path" into its parts; local paths (including ./x:y) are left alone
+fn split_pod_path(arg: &str) -> Option<(&str, &str)> {
+    let (pod, path) = arg.split_once(':')?;
+    
+    if pod.is_empty() || pod.contains('/') || pod.starts_with('.') {
+        return None;
+    }
+    
+    Some((pod, path))
+}
+
+fn switch_context(pattern: Option<&str>) -> Result<()> {
+    let output = run_kubectl(&["config", "get-contexts", "-o", "name"])?;
+    if !output.status.success() {
+        anyhow::bail!("Failed to list contexts: {}", String::from_utf8_lossy(&output.stderr).trim());
+    }
+    let contexts: Vec<String> = String::from_utf8_lossy(&output.stdout).lines().map(String::from).collect();
+    
+    let current = run_kubectl(&["config", "current-context"])
+        .ok()
+        .filter(|output| output.status.success())
+        .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string());
+    
+    let Some(pattern) = pattern else {
+        for context in &contexts {
+            if current.as_ref() == Some(context) {
+                println!("{} {}", "*".success().bold(), context.success().bold());
+            } else {
+                println!("  {}", context);
+            }
+        }
+        if let Some(pinned) = default_context() {
+            println!("\n{} kdbg itself uses context {} (--context, KDBG_CONTEXT, .kdbg or the config file)", 
+                "[INFO]".accent(), pinned.bold());
+        }
+        return Ok(());
+    };
+    
+    let target = match_name("contexts", &contexts, pattern)?;
+    
+    if current.as_deref() == Some(target) {
+        println!("{} Already on context {}", "[INFO]".accent(), target.bold());
+        return Ok(());
+    }
+    
+    if is_dry_run() {
+        println!("{} Would run: kubectl config use-context {}", "[DRY RUN]".warning(), target);
+        return Ok(());
+    }
+    
+    let output = run_kubectl(&["config", "use-context", target])?;
+    if !output.status.success() {
+        anyhow::bail!("Failed to switch context: {}", String::from_utf8_lossy(&output.stderr).trim());
+    }
+    println!("{} Switched to context {}", "[SUCCESS]".success(), target.bold());
+    
+    if let Some(pinned) = default_context().filter(|pinned| *pinned != target) {
+        println!("{} kdbg commands still use context {} (--context, KDBG_CONTEXT, .kdbg or the config file)", 
+            "[WARN]".warning(), pinned.bold());
+    }
+    
+    Ok(())
+}
+
+// Substring matching like pod names, except that an exact name wins, so
+// `prod` can be picked even when `prod-eu` exists
+fn match_name<'a>(kind: &str, names: &'a [String], pattern: &str) -> Result<&'a str> {
+    let matches: Vec<&String> = match names.iter().find(|name| *name == pattern) {
+        Some(exact) => vec![exact],
+        None => names.iter().filter(|name| name.contains(pattern)).collect(),
+    };
+    
+    match matches.as_slice() {
+        [] => anyhow::bail!("No {} found matching '{}'", kind, pattern),
+        [only] => Ok(only.as_str()),
+        _ => {
+            println!("{} Multiple {} found:", "[INFO]".warning(), kind);
+            for name in &matches {
+                println!("  - {}", name.accent());
+            }
+            anyhow::bail!("Please be more specific");
+        }
+    }
+}
+
+fn switch_namespace(pattern: Option<&str>) -> Result<()> {
+    let namespaces = list_namespaces()?;
+    
+    // A context without a namespace means kubectl uses "default"
+    let current = run_kubectl(&["config", "view", "--minify", "-o", "jsonpath={..namespace}"])
+        .ok()
+        .filter(|output| output.status.success())
+        .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string())
+        .filter(|ns| !ns.is_empty())
+        .unwrap_or_else(|| "default".to_string());
+    let pinned = env_or_config("KDBG_NAMESPACE", "namespace");
+    
+    let Some(pattern) = pattern else {
+        for ns in &namespaces {
+            if *ns == current {
+                println!("{} {}", "*".success().bold(), ns.success().bold());
+            } else {
+                println!("  {}", namespace_colored(ns, ns));
+            }
+        }
+        if let Some(pinned) = pinned {
+            println!("\n{} kdbg itself defaults to namespace {} (KDBG_NAMESPACE, .kdbg or the config file)", 
+                "[INFO]".accent(), pinned.bold());
+        }
+        return Ok(());
+    };
+    
+    let target = match_name("namespaces", &namespaces, pattern)?;
+    
+    if current == target {
+        println!("{} Already using namespace {}", "[INFO]".accent(), target.bold());
+        return Ok(());
+    }
+    
+    let namespace_arg = format!("--namespace={}", target);
+    if is_dry_run() {
+        println!("{} Would run: kubectl config set-context --current {}", "[DRY RUN]".warning(), namespace_arg);
+        return Ok(());
+    }
+    
+    let output = run_kubectl(&["config", "set-context", "--current", &namespace_arg])?;
+    if !output.status.success() {
+        anyhow::bail!("Failed to switch namespace: {}", String::from_utf8_lossy(&output.stderr).trim());
+    }
+    println!("{} Default namespace is now {}", "[SUCCESS]".success(), target.bold());
+    
+    // kdbg's own lookups search every namespace unless told otherwise
+    match pinned {
+        Some(pinned) if pinned != target => println!("{} kdbg commands still default to namespace {} (KDBG_NAMESPACE, .kdbg or the config file)", 
+            "[WARN]".warning(), pinned.bold()),
+        Some(_) => {}
+        None => println!("{} kdbg commands without -n still search all namespaces; set namespace in .kdbg or the config file to change that", 
+            "[INFO]".accent()),
+    }
+    
+    Ok(())
+}
+
+fn complete_values(kind: &str, args: &[&str]) -> Result<()> {
+    use std::time::Duration;
+    
+    // kubectl falls back to its current context and $KUBECONFIG, so those
+    // pick the cache too when kdbg doesn't pin them
+    let context = default_context().map(String::from).or_else(|| {
+        run_kubectl(&["config", "current-context"])
+            .ok()
+            .filter(|output| output.status.success())
+            .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string())
+    });
+    let kubeconfig = KUBECONFIG
+        .get()
+        .cloned()
+        .flatten()
+        .or_else(|| std::env::var("KUBECONFIG").ok());
+    let cache = completion_cache(kind, context.as_deref(), kubeconfig.as_deref());
+    
+    let is_fresh = std::fs::metadata(&cache)
+        .and_then(|m| m.modified())
+        .ok()
+        .and_then(|modified| modified.elapsed().ok())
+        .map(|age| age < Duration::from_secs(COMPLETION_CACHE_SECS))
+        .unwrap_or(false);
+    
+    if is_fresh && let Ok(cached) = std::fs::read_to_string(&cache) {
+        print!("{}", cached);
+        return Ok(());
+    }
+    
+    let output = kubectl(args)
+        .stderr(Stdio::null())
+        .output()?;
+    
+    // Completion must never print errors into the user's prompt
+    if !output.status.success() {
+        return Ok(());
+    }
+    
+    let values: String = String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(|line| format!("{}\n", line.trim_start_matches("namespace/")))
+        .collect();
+    
+    let _ = std::fs::write(&cache, &values);
+    print!("{}", values);
+    
+    Ok(())
+}
+
+// One cache file per kind and cluster, so switching contexts or kubeconfigs
+// never completes another cluster's names. Context names and paths hold
+// characters file names can't, hence the hash.
+fn completion_cache(kind: &str, context: Option<&str>, kubeconfig: Option<&str>) -> std::path::PathBuf {
+    use std::hash::{Hash, Hasher};
+    
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    (context, kubeconfig).hash(&mut hasher);
+    std::env::temp_dir().join(format!("kdbg-complete-{}-{:016x}", kind, hasher.finish()))
+}
+
+// Scripts are generated from the clap definitions so new flags show up
+// without touching this code. Namespaces are completed live through the
+// hidden __complete_namespaces helper.
+fn completion_script(shell: Shell) -> String {
+    use std::fmt::Write;
+    
+    let mut cli = Cli::command();
+    cli.build();
+    
+    let subcommands: Vec<&clap::Command> = cli.get_subcommands().filter(|c| !c.is_hide_set()).collect();
+    let names: Vec<&str> = subcommands.iter().map(|c| c.get_name()).collect();
+    let flags = |cmd: &clap::Command| -> Vec<String> {
+        cmd.get_arguments()
+            .filter(|a| !a.is_hide_set() && !a.is_positional())
+            .flat_map(|a| {
+                let short = a.get_short().map(|c| format!("-{}", c));
+                let long = a.get_long().map(|l| format!("--{}", l));
+                short.into_iter().chain(long)
+            })
+            .collect()
+    };
+    
+    let mut out = String::new();
+    
+    match shell {
+        Shell::Bash => {
+            out.push_str("_kdbg() {\n");
+            out.push_str("    local cur=\"${COMP_WORDS[COMP_CWORD]}\" prev=\"${COMP_WORDS[COMP_CWORD-1]}\"\n");
+            out.push_str("    case \"$prev\" in\n");
+            out.push_str("        -n|--namespace)\n");
+            out.push_str("            COMPREPLY=($(compgen -W \"$(kdbg __complete_namespaces 2>/dev/null)\" -- \"$cur\"))\n");
+            out.push_str("            return ;;\n");
+            out.push_str("    esac\n");
+            out.push_str("    if [[ $COMP_CWORD -eq 1 ]]; then\n");
+            writeln!(out, "        COMPREPLY=($(compgen -W \"{}\" -- \"$cur\"))", names.join(" ")).unwrap();
+            out.push_str("        return\n");
+            out.push_str("    fi\n");
+            out.push_str("    [[ $cur == -* ]] || return\n");
+            out.push_str("    local opts\n");
+            out.push_str("    case \"${COMP_WORDS[1]}\" in\n");
+            for cmd in &subcommands {
+                writeln!(out, "        {}) opts=\"{}\" ;;", cmd.get_name(), flags(cmd).join(" ")).unwrap();
+            }
+            out.push_str("    esac\n");
+            out.push_str("    COMPREPLY=($(compgen -W \"$opts\" -- \"$cur\"))\n");
+            out.push_str("}\n");
+            out.push_str("complete -o default -F _kdbg kdbg\n");
+        }
+        Shell::Zsh => {
+            out.push_str("#compdef kdbg\n\n");
+            out.push_str("_kdbg() {\n");
+            out.push_str("    local -a opts\n");
+            out.push_str("    case $words[CURRENT-1] in\n");
+            out.push_str("        -n|--namespace)\n");
+            out.push_str("            compadd -- ${(f)\"$(kdbg __complete_namespaces 2>/dev/null)\"}\n");
+            out.push_str("            return ;;\n");
+            out.push_str("    esac\n");
+            out.push_str("    if (( CURRENT == 2 )); then\n");
+            writeln!(out, "        compadd -- {}", names.join(" ")).unwrap();
+            out.push_str("        return\n");
+            out.push_str("    fi\n");
+            out.push_str("    case $words[2] in\n");
+            for cmd in &subcommands {
+                writeln!(out, "        {}) opts=({}) ;;", cmd.get_name(), flags(cmd).join(" ")).unwrap();
+            }
+            out.push_str("    esac\n");
+            out.push_str("    if [[ $PREFIX == -* ]]; then\n");
+            out.push_str("        compadd -- $opts\n");
+            out.push_str("    else\n");
+            out.push_str("        _files\n");
+            out.push_str("    fi\n");
+            out.push_str("}\n\n");
+            out.push_str("_kdbg \"$@\"\n");
+        }
+        Shell::Fish => {
+            let quote = |text: String| format!("'{}'", text.replace('\\', "\\\\").replace('\'', "\\'"));
+            let about = |cmd: &clap::Command| cmd.get_about().map(|a| a.to_string()).unwrap_or_default();
+            
+            for cmd in &subcommands {
+                writeln!(out, "complete -c kdbg -f -n __fish_use_subcommand -a {} -d {}", 
+                    cmd.get_name(), quote(about(cmd))).unwrap();
+            }
+            for cmd in &subcommands {
+                for arg in cmd.get_arguments().filter(|a| !a.is_hide_set() && !a.is_positional()) {
+                    write!(out, "complete -c kdbg -n '__fish_seen_subcommand_from {}'", cmd.get_name()).unwrap();
+                    if let Some(short) = arg.get_short() {
+                        write!(out, " -s {}", short).unwrap();
+                    }
+                    if let Some(long) = arg.get_long() {
+                        write!(out, " -l {}", long).unwrap();
+                    }
+                    if arg.get_long() == Some("namespace") {
+                        out.push_str(" -x -a '(kdbg __complete_namespaces 2>/dev/null)'");
+                    }
+                    let help = arg.get_help().map(|h| h.to_string()).unwrap_or_default();
+                    writeln!(out, " -d {}", quote(help)).unwrap();
+                }
+            }
+        }
+    }
+    
+    out
+}
+
+fn install_completions(shell: Option<Shell>) -> Result<()> {
+    let shell = match shell {
+        Some(shell) => shell,
+        None => {
+            let login_shell = std::env::var("SHELL").unwrap_or_default();
+            match login_shell.rsplit('/').next().unwrap_or("") {
+                "bash" => Shell::Bash,
+                "zsh" => Shell::Zsh,
+                "fish" => Shell::Fish,
+                "" => anyhow::bail!("$SHELL is not set; pass the shell explicitly: kdbg completions <bash|zsh|fish> --install"),
+                other => anyhow::bail!("Unsupported shell '{}'; supported: bash, zsh, fish", other),
+            }
+        }
+    };
+    
+    let home = std::env::var_os("HOME")
+        .map(PathBuf::from)
+        .ok_or_else(|| anyhow::anyhow!("$HOME is not set"))?;
+    let xdg = |var: &str, fallback: &str| {
+        std::env::var_os(var).map(PathBuf::from).unwrap_or_else(|| home.join(fallback))
+    };
+    
+    let path = match shell {
+        Shell::Bash => xdg("XDG_DATA_HOME", ".local/share").join("bash-completion/completions/kdbg"),
+        Shell::Zsh => home.join(".zsh/completions/_kdbg"),
+        Shell::Fish => xdg("XDG_CONFIG_HOME", ".config").join("fish/completions/kdbg.fish"),
+    };
+    
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(&path, completion_script(shell))?;
+    
+    println!("{} Installed completions: {}", "[SUCCESS]".success(), path.display());
+    match shell {
+        Shell::Bash => {
+            println!("{} Loaded automatically by bash-completion in new shells", "[INFO]".accent());
+            println!("{} Without bash-completion, add to ~/.bashrc: source {}", "[INFO]".accent(), path.display());
+        }
+        Shell::Zsh => {
+            println!("{} Add to ~/.zshrc (before any existing compinit):", "[INFO]".accent());
+            println!("    fpath=(~/.zsh/completions $fpath)");
+            println!("    autoload -U compinit && compinit");
+        }
+        Shell::Fish => println!("{} Loaded automatically in new fish shells", "[INFO]".accent()),
+    }
+    
+    Ok(())
+}
+
+const DEFAULT_HISTORY_MAX_ENTRIES: usize = 1000;
+
+fn state_dir() -> Option<PathBuf> {
+    let base = std::env::var_os("XDG_STATE_HOME")
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".local/state")))?;
+    
+    Some(base.join("kdbg"))
+}
+
+fn history_path() -> Option<PathBuf> {
+    state_dir().map(|dir| dir.join("history.jsonl"))
+}
+
+// Shells that worked, keyed by image digest so a rebuilt image is probed again
+fn shell_cache_path() -> Option<PathBuf> {
+    state_dir().map(|dir| dir.join("shells.json"))
+}
+
+// The digest the container runs (imageID), or the image reference while it's
+// not known yet
+fn container_image_key(status: &Value) -> Option<String> {
+    status["imageID"].as_str()
+        .filter(|id| !id.is_empty())
+        .or_else(|| status["image"].as_str())
+        .map(String::from)
+}
+
+fn load_shell_cache() -> serde_json::Map<String, Value> {
+    shell_cache_path()
+        .and_then(|path| std::fs::read_to_string(path).ok())
+        .and_then(|text| serde_json::from_str(&text).ok())
+        .unwrap_or_default()
+}
+
+fn cached_shell(image: &str) -> Option<String> {
+    load_shell_cache().get(image)?.as_str().map(String::from)
+}
+
+// Best effort like history: a cache that can't be written just means probing next time
+fn remember_shell(image: &str, shell: &str) {
+    let Some(path) = shell_cache_path() else {
+        return;
+    };
+    let mut cache = load_shell_cache();
+    if cache.get(image).and_then(Value::as_str) == Some(shell) {
+        return;
+    }
+    cache.insert(image.to_string(), Value::from(shell));
+    
+    if let Some(dir) = path.parent() {
+        let _ = std::fs::create_dir_all(dir);
+    }
+    let _ = std::fs::write(&path, Value::Object(cache).to_string());
+}
+
+fn history_enabled() -> bool {
+    config().get_bool("history.enabled").unwrap_or(false)
+}
+
+// One JSON line per invocation, recorded at dispatch so every command is
+// covered. Failing to write history must never fail the command itself.
+fn record_history(matches: &clap::ArgMatches) {
+    let Some((verb, args)) = matches.subcommand() else {
+        return;
+    };
+    if !history_enabled() || verb.starts_with("__") || matches!(verb, "history" | "completions") {
+        return;
+    }
+    let Some(path) = history_path() else {
+        return;
+    };
+    
+    let pod = args.try_get_one::<String>("pod").ok().flatten();
+    let namespace = args.try_get_many::<String>("namespace")
+        .ok()
+        .flatten()
+        .map(|values| values.cloned().collect::<Vec<_>>().join(","))
+        .filter(|ns| !ns.is_empty())
+        .or_else(|| env_or_config("KDBG_NAMESPACE", "namespace"));
+    let command: Vec<String> = std::env::args().skip(1).map(|arg| shell_quote(&arg)).collect();
+    
+    let entry = serde_json::json!({
+        "time": chrono::Local::now().to_rfc3339_opts(chrono::SecondsFormat::Secs, false),
+        "verb": verb,
+        "pod": pod,
+        "namespace": namespace,
+        "context": default_context(),
+        "command": format!("kdbg {}", command.join(" ")),
+    });
+    
+    let max_entries = config().get_u64("history.max_entries")
+        .map(|n| n as usize)
+        .unwrap_or(DEFAULT_HISTORY_MAX_ENTRIES);
+    let existing = std::fs::read_to_string(&path).unwrap_or_default();
+    let mut lines: Vec<&str> = existing.lines().collect();
+    let line = entry.to_string();
+    lines.push(&line);
+    let keep = &lines[lines.len().saturating_sub(max_entries)..];
+    
+    if let Some(dir) = path.parent() {
+        let _ = std::fs::create_dir_all(dir);
+    }
+    let _ = std::fs::write(&path, format!("{}\n", keep.join("\n")));
+}
+
+fn show_history(limit: usize) -> Result<()> {
+    if !history_enabled() {
+        println!("{} History is off; enable it in the config file:", "[INFO]".accent());
+        println!("    [history]");
+        println!("    enabled = true");
+        println!("    max_entries = {}", DEFAULT_HISTORY_MAX_ENTRIES);
+    }
+    
+    let text = history_path()
+        .and_then(|path| std::fs::read_to_string(path).ok())
+        .unwrap_or_default();
+    let entries: Vec<Value> = text.lines().filter_map(|line| serde_json::from_str(line).ok()).collect();
+    
+    if entries.is_empty() {
+        println!("No history yet");
+        return Ok(());
+    }
+    
+    println!("{:<26} {:<20} {:<15} COMMAND", "TIME", "CONTEXT", "NAMESPACE");
+    println!("{}", "-".repeat(100));
+    
+    for entry in &entries[entries.len().saturating_sub(limit)..] {
+        let field = |key: &str| entry[key].as_str().unwrap_or("-").to_string();
+        println!("{:<26} {} {} {}", 
+            field("time").muted(), pad(&field("context"), 20), pad(&field("namespace"), 15).muted(), field("command"));
+    }
+    
+    Ok(())
+}
+
+// Settings from ~/.config/kdbg/config.toml (or $KDBG_CONFIG). Only the TOML
+// subset kdbg needs is understood: [sections] and `key = value` lines.
+// Keys inside a section are stored as "section.key".
+#[derive(Default)]
+struct Config {
+    values: BTreeMap<String, String>,
+}
+
+fn config() -> &'static Config {
+    static CONFIG: OnceLock<Config> = OnceLock::new();
+    
+    CONFIG.get_or_init(|| {
+        config_path()
+            .and_then(|path| std::fs::read_to_string(path).ok())
+            .map(|text| Config::parse(&text))
+            .unwrap_or_default()
+    })
+}
+
+// Per-project defaults from the first .kdbg file found walking up from the
+// working directory, the way git finds .gitignore
+fn project_config() -> &'static Config {
+    static PROJECT_CONFIG: OnceLock<Config> = OnceLock::new();
+    
+    PROJECT_CONFIG.get_or_init(|| {
+        project_config_path()
+            .and_then(|path| std::fs::read_to_string(path).ok())
+            .map(|text| Config::parse(&text))
+            .unwrap_or_default()
+    })
+}
+
+fn project_config_path() -> Option<PathBuf> {
+    let cwd = std::env::current_dir().ok()?;
+    
+    cwd.ancestors()
+        .map(|dir| dir.join(".kdbg"))
+        .find(|path| path.is_file())
+}
+
+fn config_path() -> Option<PathBuf> {
+    if let Some(path) = std::env::var_os("KDBG_CONFIG") {
+        return Some(PathBuf::from(path));
+    }
+    
+    let base = std::env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+    
+    Some(base.join("kdbg").join("config.toml"))
+}
+
+impl Config {
+    fn parse(text: &str) -> Config {
+        let mut values = BTreeMap::new();
+        let mut section = String::new();
+        
+        for line in text.lines() {
+            let line = strip_toml_comment(line).trim();
+            
+            if line.is_empty() {
+                continue;
+            }
+            
+            if let Some(name) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+                section = name.trim().to_string();
+                continue;
+            }
+            
+            if let Some((key, value)) = line.split_once('=') {
+                let key = key.trim().trim_matches('"');
+                let key = if section.is_empty() { key.to_string() } else { format!("{}.{}", section, key) };
+                values.insert(key, value.trim().to_string());
+            }
+        }
+        
+        Config { values }
+    }
+    
+    fn get(&self, key: &str) -> Option<String> {
+        self.values.get(key).map(|value| unquote(value))
+    }
+    
+    fn get_u64(&self, key: &str) -> Option<u64> {
+        self.get(key)?.parse().ok()
+    }
+    
+    fn get_bool(&self, key: &str) -> Option<bool> {
+        self.get(key)?.parse().ok()
+    }
+    
+    // Key names under a [section], e.g. the presets defined in [exec_presets]
+    fn section_keys(&self, section: &str) -> Vec<String> {
+        let prefix = format!("{}.", section);
+        self.values.keys().filter_map(|key| key.strip_prefix(&prefix)).map(String::from).collect()
+    }
+    
+    // Single-line TOML arrays of strings, e.g. shells = ["/bin/bash", "/bin/sh"]
+    fn get_list(&self, key: &str) -> Option<Vec<String>> {
+        let value = self.values.get(key)?;
+        let items = value.strip_prefix('[')?.strip_suffix(']')?;
+        
+        Some(items
+            .split(',')
+            .map(unquote)
+            .filter(|item| !item.is_empty())
+            .collect())
+    }
+}
+
+fn strip_toml_comment(line: &str) -> &str {
+    let mut quote: Option<char> = None;
+    
+    for (i, c) in line.char_indices() {
+        match (c, quote) {
+            ('"' | '\'', None) => quote = Some(c),
+            (c, Some(q)) if c == q => quote = None,
+            ('#', None) => return &line[..i],
+            _ => {}
+        }
+    }
+    
+    line
+}
+
+fn unquote(value: &str) -> String {
+    let value = value.trim();
+    
+    if value.len() >= 2 && value.starts_with('"') && value.ends_with('"') {
+        value[1..value.len() - 1].replace("\\\"", "\"")
+    } else if value.len() >= 2 && value.starts_with('\'') && value.ends_with('\'') {
+        value[1..value.len() - 1].to_string()
+    } else {
+        value.to_string()
+    }
+}
+
+fn calculate_age(timestamp: &str) -> String {
+    use std::time::{SystemTime, UNIX_EPOCH};
+    
+    let created = chrono::DateTime::parse_from_rfc3339(timestamp)
+        .map(|dt| dt.timestamp())
+        .unwrap_or(0);
+    
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs() as i64;
+    
+    let diff = now - created;
+    
+    if diff < 60 {
+        format!("{}s", diff)
+    } else if diff < 3600 {
+        format!("{}m", diff / 60)
+    } else if diff < 86400 {
+        format!("{}h", diff / 3600)
+    } else {
         format!("{}d", diff / 86400)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::sync::Mutex;
+    
+    // colored's override is process-wide and tests run in parallel
+    static COLORS: Mutex<()> = Mutex::new(());
+    
+    fn with_colors<T>(enabled: bool, f: impl FnOnce() -> T) -> T {
+        let _guard = COLORS.lock().unwrap_or_else(|e| e.into_inner());
+        colored::control::set_override(enabled);
+        let result = f();
+        colored::control::unset_override();
+        result
+    }
+    
+    fn list_args(args: &[&str]) -> ListArgs {
+        let cli = Cli::try_parse_from(["kdbg", "list"].iter().chain(args)).unwrap();
+        match cli.command {
+            Commands::List(list) => list,
+            _ => unreachable!(),
+        }
+    }
+    
+    fn logs_args(args: &[&str]) -> LogsArgs {
+        let cli = Cli::try_parse_from(["kdbg", "logs"].iter().chain(args)).unwrap();
+        match cli.command {
+            Commands::Logs(logs) => logs,
+            _ => unreachable!(),
+        }
+    }
+    
+    fn sample_pods() -> Vec<Value> {
+        vec![
+            json!({
+                "metadata": {"name": "api-7d4f8c9b5-xk2lp", "namespace": "prod", "creationTimestamp": "2026-10-14T00:00:00Z"},
+                "spec": {"nodeName": "node-1", "containers": [{"name": "app"}]},
+                "status": {"phase": "Running", "containerStatuses": [{"name": "app", "ready": true, "restartCount": 7, "state": {"running": {}}}]}
+            }),
+            json!({
+                "metadata": {"name": "worker-0", "namespace": "team-a", "creationTimestamp": "2026-10-14T00:00:00Z"},
+                "spec": {"containers": [{"name": "worker"}]},
+                "status": {"phase": "Pending", "containerStatuses": [{"name": "worker", "ready": false, "restartCount": 0, "state": {"waiting": {"reason": "CrashLoopBackOff"}}}]}
+            }),
+        ]
+    }
+    
+    #[test]
+    fn shell_quote_leaves_safe_values_alone() {
+        assert_eq!(shell_quote("api-7d4f8c9b5-xk2lp"), "api-7d4f8c9b5-xk2lp");
+        assert_eq!(shell_quote("KEY=a/b:c@d"), "KEY=a/b:c@d");
+        assert_eq!(shell_quote(""), "''");
+    }
+    
+    #[test]
+    fn shell_quote_quotes_special_characters() {
+        assert_eq!(shell_quote("it's"), "'it'\\''s'");
+        assert_eq!(shell_quote("two words"), "'two words'");
+        assert_eq!(shell_quote("$HOME"), "'$HOME'");
+        assert_eq!(shell_quote("a; rm -rf /"), "'a; rm -rf /'");
+    }
+    
+    #[test]
+    fn shell_quote_round_trips_through_sh() {
+        for value in ["it's", "two words", "$HOME `id`", "a; b && c", "'\"'\\"] {
+            let output = Command::new("sh")
+                .args(["-c", &format!("printf %s {}", shell_quote(value))])
+                .output()
+                .unwrap();
+            assert_eq!(String::from_utf8_lossy(&output.stdout), value);
+        }
+    }
+    
+    #[test]
+    fn top_error_only_blames_the_namespace_when_it_is_missing() {
+        let missing_ns = top_error(r#"Error from server (NotFound): namespaces "nope" not found"#);
+        assert!(missing_ns.to_string().starts_with("Namespace not found"));
+        
+        for stderr in [
+            r#"Error from server (NotFound): pods "api" not found"#,
+            "error: the server could not find the requested resource (get pods.metrics.k8s.io)",
+        ] {
+            assert!(top_error(stderr).to_string().starts_with("Failed to get resource usage"), "{}", stderr);
+        }
+    }
+    
+    #[test]
+    fn pad_counts_wide_characters_as_two_columns() {
+        for (text, width) in [
+            ("日本語", 6), ("팀-a", 4), ("ｐｒｏｄ", 8), ("🔥", 2), ("🚀 api", 6), ("🤖", 2), ("🫠", 2),
+            ("⚡", 2), ("✅", 2), ("⌚", 2), ("⭐", 2), ("🀄", 2), ("🇩🇪", 2),
+        ] {
+            assert_eq!(display_width(text), width, "{}", text);
+            assert_eq!(display_width(&pad(text, 12)), 12, "{}", text);
+            assert_eq!(pad(text, 12).chars().count(), 12 - (width - text.chars().count()), "{}", text);
+        }
+    }
+    
+    #[test]
+    fn pad_ignores_zero_width_characters() {
+        let combined = "cafe\u{301}";
+        assert_eq!(display_width(combined), 4);
+        assert_eq!(pad(combined, 6), format!("{}  ", combined));
+        assert_eq!(display_width("a\u{200b}b"), 2);
+        assert_eq!(char_width('\u{85}'), 0);
+        assert_eq!(char_width('\u{9f}'), 0);
+        assert_eq!(display_width("a\u{85}b"), 2);
+    }
+    
+    #[test]
+    fn pad_never_truncates() {
+        assert_eq!(pad("日本語のネームスペース", 4), "日本語のネームスペース");
+    }
+    
+    #[test]
+    fn parse_cpu_handles_suffixes() {
+        assert_eq!(parse_cpu("250m"), Some(250));
+        assert_eq!(parse_cpu("1500000n"), Some(1));
+        assert_eq!(parse_cpu("123456789n"), Some(123));
+        assert_eq!(parse_cpu("300u"), Some(0));
+        assert_eq!(parse_cpu("2500u"), Some(2));
+        assert_eq!(parse_cpu("1"), Some(1000));
+        assert_eq!(parse_cpu("0.5"), Some(500));
+    }
+    
+    #[test]
+    fn parse_memory_handles_binary_and_decimal_suffixes() {
+        assert_eq!(parse_memory("1Ki"), Some(1024));
+        assert_eq!(parse_memory("128Mi"), Some(128 * 1024 * 1024));
+        assert_eq!(parse_memory("1.5Gi"), Some(3 << 29));
+        assert_eq!(parse_memory("1Ti"), Some(1 << 40));
+        assert_eq!(parse_memory("2k"), Some(2_000));
+        assert_eq!(parse_memory("300M"), Some(300_000_000));
+        assert_eq!(parse_memory("1G"), Some(1_000_000_000));
+        assert_eq!(parse_memory("4096"), Some(4096));
+    }
+    
+    #[test]
+    fn quantity_parsing_rejects_malformed_input() {
+        for value in ["", "m", "abc", "12x", "1.5m", "-1", "NaN", "inf", "--5n"] {
+            assert_eq!(parse_cpu(value), None, "{}", value);
+        }
+        for value in ["", "Mi", "abc", "12Xi", "1.5.2Gi", "-1Gi", "NaN", "infMi", "1 Gi"] {
+            assert_eq!(parse_memory(value), None, "{}", value);
+        }
+    }
+    
+    fn pod_rows(metrics: &[PodMetrics]) -> Vec<(&str, &str, u64, u64)> {
+        metrics.iter().map(|m| (m.namespace.as_str(), m.pod.as_str(), m.cpu_millicores, m.memory_bytes)).collect()
+    }
+    
+    #[test]
+    fn top_parse_reads_rows_with_and_without_namespace() {
+        let all = "NAMESPACE   NAME        CPU(cores)   MEMORY(bytes)\nprod        api-0       250m         128Mi\nteam-a      worker-0    1            1Gi\n";
+        assert_eq!(pod_rows(&top_parse(all)), [
+            ("prod", "api-0", 250, 128 << 20),
+            ("team-a", "worker-0", 1000, 1 << 30),
+        ]);
+        
+        let one = "NAME        CPU(cores)   MEMORY(bytes)\napi-0       5m           40Mi\n";
+        assert_eq!(pod_rows(&top_parse(one)), [("", "api-0", 5, 40 << 20)]);
+        assert!(top_parse("").is_empty());
+    }
+    
+    #[test]
+    fn top_parse_skips_malformed_rows() {
+        let text = "NAME        CPU(cores)   MEMORY(bytes)\napi-0       5m\napi-1       lots         40Mi\napi-2       5m           40Mi\n";
+        assert_eq!(pod_rows(&top_parse(text)), [("", "api-2", 5, 40 << 20)]);
+    }
+    
+    #[test]
+    fn top_containers_parse_reads_rows_with_and_without_namespace() {
+        let rows = |metrics: Vec<ContainerMetrics>| -> Vec<(String, String, String, u64, u64)> {
+            metrics.into_iter().map(|m| (m.namespace, m.pod, m.container, m.cpu_millicores, m.memory_bytes)).collect()
+        };
+        let row = |ns: &str, pod: &str, container: &str, cpu, mem| (ns.to_string(), pod.to_string(), container.to_string(), cpu, mem);
+        
+        let all = "NAMESPACE   POD     NAME          CPU(cores)   MEMORY(bytes)\nprod        api-0   app           420m         240Mi\nprod        api-0   istio-proxy   5m           40Mi\n";
+        assert_eq!(rows(top_containers_parse(all)), [
+            row("prod", "api-0", "app", 420, 240 << 20),
+            row("prod", "api-0", "istio-proxy", 5, 40 << 20),
+        ]);
+        
+        let one = "POD     NAME   CPU(cores)   MEMORY(bytes)\napi-0   app    420m         240Mi\napi-0   broken 5m\napi-0   sidecar ?  1Mi\n";
+        assert_eq!(rows(top_containers_parse(one)), [row("", "api-0", "app", 420, 240 << 20)]);
+    }
+    
+    #[test]
+    fn cli_definition_is_consistent() {
+        Cli::command().debug_assert();
+    }
+    
+    #[test]
+    fn global_options_parse_before_and_after_the_subcommand() {
+        for argv in [
+            &["kdbg", "--context", "x", "--kubeconfig", "/tmp/kc", "list"][..],
+            &["kdbg", "list", "--context", "x", "--kubeconfig", "/tmp/kc"],
+            &["kdbg", "--context", "x", "list", "--kubeconfig", "/tmp/kc"],
+        ] {
+            let cli = Cli::try_parse_from(argv).unwrap();
+            assert_eq!(cli.context.as_deref(), Some("x"), "{:?}", argv);
+            assert_eq!(cli.kubeconfig.as_deref(), Some("/tmp/kc"), "{:?}", argv);
+            assert!(matches!(cli.command, Commands::List(_)), "{:?}", argv);
+        }
+    }
+    
+    #[test]
+    fn namespace_applies_before_and_after_the_subcommand() {
+        for argv in [
+            &["kdbg", "-n", "prod", "list"][..],
+            &["kdbg", "list", "-n", "prod"],
+            &["kdbg", "--namespace", "prod", "logs", "api"],
+            &["kdbg", "logs", "api", "--namespace", "prod"],
+        ] {
+            let mut cli = Cli::try_parse_from(argv).unwrap();
+            apply_env_defaults(&mut cli.command, cli.namespace);
+            assert_eq!(namespace_values(&mut cli.command).cloned(), Some(vec!["prod".to_string()]), "{:?}", argv);
+        }
+    }
+    
+    #[test]
+    fn glob_match_handles_wildcards_and_anchors() {
+        for (pattern, text) in [
+            ("team-*", "team-a"), ("team-*", "team-"), ("*-prod", "eu-prod"), ("team-?", "team-b"),
+            ("*", ""), ("t*m-*", "team-a"), ("*a*", "banana"), ("prod", "prod"),
+        ] {
+            assert!(glob_match(pattern, text), "{} should match {}", pattern, text);
+        }
+        // Patterns are anchored at both ends
+        for (pattern, text) in [
+            ("team-*", "my-team-a"), ("*-prod", "eu-prod-2"), ("team-?", "team-ab"), ("team-?", "team-"),
+            ("prod", "production"), ("prod", "preprod"),
+        ] {
+            assert!(!glob_match(pattern, text), "{} shouldn't match {}", pattern, text);
+        }
+    }
+    
+    #[test]
+    fn expand_namespaces_matches_globs_against_listed_namespaces() {
+        let listed = || Ok(["default", "team-a", "team-b", "team-ab", "prod"].map(String::from).to_vec());
+        
+        assert_eq!(expand_namespaces_from("team-*", listed).unwrap(), ["team-a", "team-b", "team-ab"]);
+        assert_eq!(expand_namespaces_from("team-?", listed).unwrap(), ["team-a", "team-b"]);
+        assert_eq!(expand_namespaces_from("prod, team-?b", listed).unwrap(), ["team-ab", "prod"]);
+        
+        let error = expand_namespaces_from("staging-*", listed).unwrap_err();
+        assert_eq!(error.to_string(), "No namespaces match 'staging-*'");
+    }
+    
+    #[test]
+    fn expand_namespaces_only_lists_namespaces_for_globs() {
+        let unlisted = || -> Result<Vec<String>> { panic!("plain namespaces don't need listing") };
+        assert_eq!(expand_namespaces_from("prod", unlisted).unwrap(), ["prod"]);
+        assert_eq!(expand_namespaces_from("prod,team-a,", unlisted).unwrap(), ["prod", "team-a"]);
+    }
+    
+    #[test]
+    fn completion_cache_is_per_context_and_kubeconfig() {
+        let cache = |context, kubeconfig| completion_cache("namespaces", context, kubeconfig);
+        
+        assert_eq!(cache(Some("prod"), None), cache(Some("prod"), None));
+        assert_ne!(cache(Some("prod"), None), cache(Some("staging"), None));
+        assert_ne!(cache(Some("prod"), None), cache(Some("prod"), Some("/tmp/other")));
+        assert_ne!(cache(None, None), cache(Some(""), None));
+        assert_ne!(cache(Some("prod"), None), completion_cache("contexts", Some("prod"), None));
+        
+        let name = cache(Some("arn:aws:eks:eu-west-1:123:cluster/prod"), Some("/home/me/.kube/config"));
+        assert_eq!(name.parent(), Some(std::env::temp_dir().as_path()));
+    }
+    
+    #[test]
+    fn list_output_forwards_kubectl_formats() {
+        for spec in ["yaml", "name", "jsonpath={.items[*].metadata.name}", "go-template={{.kind}}"] {
+            assert!(matches!(ListOutput::parse(Some(spec)).unwrap(), ListOutput::Passthrough(s) if s == spec), "{}", spec);
+        }
+        assert!(matches!(ListOutput::parse(Some("json")).unwrap(), ListOutput::Json));
+        assert!(matches!(ListOutput::parse(None).unwrap(), ListOutput::Table));
+        assert!(ListOutput::parse(Some("xml")).is_err());
+    }
+    
+    #[test]
+    fn list_yaml_builds_the_kubectl_get_command() {
+        let list = list_args(&["-o", "yaml", "-l", "app=api", "--status", "Running"]);
+        let ListOutput::Passthrough(spec) = ListOutput::from_args(&list).unwrap() else {
+            panic!("-o yaml should be forwarded to kubectl");
+        };
+        assert_eq!(
+            passthrough_args(&list, Some("prod"), &spec),
+            ["get", "pods", "-n", "prod", "-l", "app=api", "--field-selector", "status.phase=Running", "-o", "yaml"],
+        );
+        assert_eq!(
+            passthrough_args(&list_args(&[]), None, "yaml"),
+            ["get", "pods", "--all-namespaces", "-o", "yaml"],
+        );
+    }
+    
+    #[test]
+    fn pod_list_has_no_ansi_escapes_when_colors_are_off() {
+        let pods = sample_pods();
+        let pods: Vec<&Value> = pods.iter().collect();
+        for args in [&[][..], &["-v"], &["-g", "namespace"]] {
+            let options = RenderOptions::from_args(&list_args(args), true);
+            let (colored, plain) = (
+                with_colors(true, || render_pod_list(&pods, &options)),
+                with_colors(false, || format!("{}{}{}", pod_list_title(), render_pod_list(&pods, &options), pod_list_total(pods.len()))),
+            );
+            assert!(colored.contains('\x1b'), "{:?}", args);
+            assert!(!plain.contains('\x1b'), "{:?}: {}", args, plain);
+            assert!(plain.contains("api-7d4f8c9b5-xk2lp") && plain.contains("Pending"), "{}", plain);
+        }
+    }
+    
+    #[test]
+    fn restart_marker_keeps_wide_columns_aligned() {
+        let pods = sample_pods();
+        let pods: Vec<&Value> = pods.iter().collect();
+        let options = RenderOptions::from_args(&list_args(&["-o", "wide"]), true);
+        let table = with_colors(false, || render_pod_list(&pods, &options));
+        let lines: Vec<&str> = table.lines().collect();
+        
+        let node_column = lines[0].find("NODE").unwrap();
+        assert_eq!(lines[2].find("node-1"), Some(node_column), "{}", table);
+        assert_eq!(lines[3].find("<none>"), Some(node_column), "{}", table);
+        assert!(lines[2].trim_end().ends_with("(⟳7)"), "{}", table);
+    }
+    
+    #[test]
+    fn json_path_follows_fields_and_indexes() {
+        let pod = json!({
+            "metadata": {"name": "api-0", "labels": {"app.kubernetes.io/name": "api"}},
+            "spec": {"containers": [{"name": "app", "image": "api:1"}, {"name": "proxy", "image": "envoy:2"}]},
+        });
+        
+        assert_eq!(json_path(&pod, ".metadata.name"), [&json!("api-0")]);
+        assert_eq!(json_path(&pod, "{.metadata.name}"), [&json!("api-0")]);
+        assert_eq!(json_path(&pod, ".spec.containers[1].image"), [&json!("envoy:2")]);
+        assert_eq!(json_path(&pod, ".spec.containers[*].name"), [&json!("app"), &json!("proxy")]);
+        assert_eq!(json_path(&pod, r".metadata.labels.app\.kubernetes\.io/name"), [&json!("api")]);
+    }
+    
+    #[test]
+    fn json_path_is_empty_for_missing_fields() {
+        let pod = json!({"metadata": {"name": "api-0"}, "spec": {"containers": [{"name": "app"}]}});
+        for path in [".metadata.uid", ".status.phase", ".spec.containers[3].name", ".spec.containers[x]", ".metadata.name[0]"] {
+            assert!(json_path(&pod, path).is_empty(), "{}", path);
+        }
+    }
+    
+    #[test]
+    fn parse_columns_reads_paths_builtins_and_shorthands() {
+        let columns = parse_columns("NAME:.metadata.name, restarts\nstatus.phase  # a comment\n.spec.nodeName").unwrap();
+        let parsed: Vec<(&str, String)> = columns
+            .iter()
+            .map(|column| {
+                let source = match &column.source {
+                    ColumnSource::Path(path) => path.clone(),
+                    ColumnSource::Builtin(name) => format!("builtin {}", name),
+                };
+                (column.header.as_str(), source)
+            })
+            .collect();
+        assert_eq!(parsed, [
+            ("NAME", ".metadata.name".to_string()),
+            ("RESTARTS", "builtin restarts".to_string()),
+            ("PHASE", ".status.phase".to_string()),
+            ("NODENAME", ".spec.nodeName".to_string()),
+        ]);
+    }
+    
+    #[test]
+    fn parse_columns_rejects_malformed_specs() {
+        for spec in ["NAME:", ":.metadata.name", "NAME:.metadata.name,:x"] {
+            let error = parse_columns(spec).err().unwrap();
+            assert!(error.to_string().starts_with("Invalid custom column"), "{}: {}", spec, error);
+        }
+        for spec in ["", " , ", "# only a comment"] {
+            assert_eq!(parse_columns(spec).err().unwrap().to_string(), "The column spec is empty", "{}", spec);
+        }
+    }
+    
+    fn dedup_output(window: usize, screen: Option<(usize, usize)>, lines: &[&str]) -> String {
+        let mut dedup = LogDedup { window, screen, recent: Default::default(), out: Vec::new() };
+        with_colors(false, || {
+            for line in lines {
+                dedup.push(line, |text| text.to_string());
+            }
+            dedup.flush();
+        });
+        String::from_utf8(dedup.out).unwrap()
+    }
+    
+    #[test]
+    fn dedup_collapses_consecutive_repeats() {
+        let output = dedup_output(1, None, &["retrying", "retrying", "retrying", "connected", "retrying"]);
+        assert_eq!(output, "retrying (x3)\nconnected\nretrying\n");
+    }
+    
+    #[test]
+    fn dedup_window_folds_interleaved_repeats() {
+        let lines = ["tick", "tock", "tick", "tock", "tick", "done"];
+        assert_eq!(dedup_output(1, None, &lines), "tick\ntock\ntick\ntock\ntick\ndone\n");
+        assert_eq!(dedup_output(2, None, &lines), "tick (x3)\ntock (x2)\ndone\n");
+        // A line that left the window starts a new count
+        assert_eq!(dedup_output(2, None, &["a", "b", "c", "a"]), "a\nb\nc\na\n");
+    }
+    
+    #[test]
+    fn dedup_flushes_held_lines_when_the_stream_ends() {
+        assert_eq!(dedup_output(3, None, &["a", "b", "a"]), "a (x2)\nb\n");
+        assert_eq!(dedup_output(1, None, &[]), "");
+    }
+    
+    #[test]
+    fn dedup_rewrites_counts_in_place_on_a_terminal() {
+        let output = dedup_output(1, Some((24, 80)), &["retrying", "retrying", "connected"]);
+        assert_eq!(output, "retrying\n\x1b[1A\rretrying (x2)\x1b[1B\rconnected\n");
+    }
+    
+    #[test]
+    fn json_lines_frames_tag_added_changed_and_removed_pods() {
+        let pod = |uid: &str, phase: &str| json!({"metadata": {"name": format!("pod-{}", uid), "uid": uid}, "status": {"phase": phase}});
+        let events = |out: &str| -> Vec<(String, Option<String>)> {
+            out.lines()
+                .map(|line| serde_json::from_str::<Value>(line).unwrap())
+                .map(|pod| (pod["metadata"]["uid"].as_str().unwrap().to_string(), pod["_event"].as_str().map(String::from)))
+                .collect()
+        };
+        let event = |uid: &str, event: Option<&str>| (uid.to_string(), event.map(String::from));
+        
+        let (out, first) = json_lines_frame(None, vec![pod("a", "Running"), pod("b", "Pending")]);
+        assert_eq!(events(&out), [event("a", None), event("b", None)]);
+        
+        let (out, second) = json_lines_frame(Some(&first), vec![pod("a", "Running"), pod("b", "Running"), pod("c", "Pending")]);
+        assert_eq!(events(&out), [event("a", None), event("b", Some("changed")), event("c", Some("added"))]);
+        
+        let (out, _) = json_lines_frame(Some(&second), vec![pod("c", "Pending")]);
+        assert_eq!(events(&out), [event("c", None), event("a", Some("removed")), event("b", Some("removed"))]);
+    }
+    
+    #[test]
+    fn json_lines_frames_match_pods_by_uid() {
+        // A StatefulSet pod recreated under the same name is a new pod
+        let old = json!({"metadata": {"name": "web-0", "namespace": "prod", "uid": "1"}});
+        let new = json!({"metadata": {"name": "web-0", "namespace": "prod", "uid": "2"}});
+        let (_, previous) = json_lines_frame(None, vec![old]);
+        let (out, _) = json_lines_frame(Some(&previous), vec![new]);
+        let events: Vec<Value> = out.lines().map(|line| serde_json::from_str::<Value>(line).unwrap()["_event"].clone()).collect();
+        assert_eq!(events, [json!("added"), json!("removed")]);
+    }
+    
+    #[test]
+    fn parse_ordinal_pattern_maps_hash_to_the_pod_name() {
+        assert_eq!(parse_ordinal_pattern("web#2").unwrap().as_deref(), Some("web-2"));
+        assert_eq!(parse_ordinal_pattern("my-db#0").unwrap().as_deref(), Some("my-db-0"));
+        assert_eq!(parse_ordinal_pattern("web").unwrap(), None);
+        assert_eq!(parse_ordinal_pattern("web-2").unwrap(), None);
+        for pattern in ["web#", "#2", "web#x", "web#-1"] {
+            assert!(parse_ordinal_pattern(pattern).is_err(), "{}", pattern);
+        }
+    }
+    
+    #[test]
+    fn ordinal_flag_conflicts_with_a_hash_in_the_pattern() {
+        assert_eq!(with_ordinal("web", Some(3)).unwrap(), "web#3");
+        assert_eq!(with_ordinal("web#2", None).unwrap(), "web#2");
+        let error = with_ordinal("web#2", Some(3)).unwrap_err();
+        assert!(error.to_string().contains("use either NAME#N or --ordinal"), "{}", error);
+    }
+    
+    #[test]
+    fn log_container_resolves_init_to_the_init_container() {
+        let pod = json!({
+            "metadata": {"name": "api-0"},
+            "spec": {"initContainers": [{"name": "migrate"}], "containers": [{"name": "app"}]},
+        });
+        for args in [&["api", "--init"][..], &["api", "-c", "init"]] {
+            assert_eq!(log_container(&pod, &logs_args(args)).unwrap().as_deref(), Some("migrate"), "{:?}", args);
+        }
+        assert_eq!(log_container(&pod, &logs_args(&["api"])).unwrap(), None);
+        assert_eq!(log_container(&pod, &logs_args(&["api", "-c", "app"])).unwrap().as_deref(), Some("app"));
+        
+        let no_init = json!({"metadata": {"name": "api-0"}, "spec": {"containers": [{"name": "app"}]}});
+        assert!(log_container(&no_init, &logs_args(&["api", "--init"])).is_err());
+    }
+    
+    #[test]
+    fn log_container_prefers_a_container_named_init() {
+        for pod in [
+            json!({"metadata": {"name": "api-0"}, "spec": {"initContainers": [{"name": "init"}, {"name": "migrate"}], "containers": [{"name": "app"}]}}),
+            json!({"metadata": {"name": "api-0"}, "spec": {"initContainers": [{"name": "migrate"}], "containers": [{"name": "app"}, {"name": "init"}]}}),
+        ] {
+            for args in [&["api", "--init"][..], &["api", "-c", "init"]] {
+                assert_eq!(log_container(&pod, &logs_args(args)).unwrap().as_deref(), Some("init"), "{:?}", args);
+            }
+        }
+    }
+    
+    #[test]
+    fn pager_keeps_color_codes_of_colored_lines() {
+        let lines: Vec<String> = with_colors(true, || {
+            [r#"{"level":"error","msg":"db down"}"#, r#"{"level":"warn","msg":"slow query"}"#, "plain line"]
+                .into_iter()
+                .map(pretty_json_line)
+                .collect()
+        });
+        assert!(lines[0].contains('\x1b') && lines[1].contains('\x1b'));
+        
+        let mut pager = Pager::new(lines.clone());
+        (pager.rows, pager.cols) = (10, 200);
+        let frame = pager.frame();
+        for line in &lines {
+            assert!(frame.contains(line.as_str()), "{:?} missing from {:?}", line, frame);
+        }
+        assert!(pager.plain.iter().all(|line| !line.contains('\x1b')));
+        
+        // A search match is drawn from the plain text, with only the highlight added
+        pager.search = Some("db down".to_string());
+        let frame = pager.frame();
+        assert!(frame.contains("\x1b[7mdb down\x1b[27m"));
+        assert!(!frame.contains(lines[0].as_str()));
+        assert!(frame.contains(lines[1].as_str()));
+    }
+    
+    #[test]
+    fn truncate_to_width_copies_escape_codes_without_counting_them() {
+        let line = format!("\x1b[31m{}\x1b[0m", "x".repeat(10));
+        // The pager resets colors after every line itself
+        assert_eq!(truncate_to_width(&line, 4), "\x1b[31mxxxx");
+        assert_eq!(truncate_to_width(&line, 20), line);
+    }
+    
+    #[test]
+    fn restart_thresholds_are_inclusive() {
+        let thresholds = RestartThresholds { warn: DEFAULT_RESTART_WARN, crit: DEFAULT_RESTART_THRESHOLD };
+        let colors: Vec<Option<Color>> = with_colors(true, || {
+            [0, 1, 2, 3, 4, 5, 9].into_iter().map(|n| thresholds.colored(n, "n").fgcolor).collect()
+        });
+        let (warning, danger) = (Some(theme().warning), Some(theme().danger));
+        assert_eq!(colors, [None, None, None, warning, warning, danger, danger]);
+    }
+    
+    #[test]
+    fn events_match_pods_by_uid_then_name() {
+        let pod = json!({"metadata": {"name": "web-0", "uid": "new"}});
+        let event = |object: Value| json!({"involvedObject": object});
+        
+        assert!(is_event_for(&event(json!({"name": "web-0", "uid": "new"})), &pod));
+        // A StatefulSet's previous web-0 has the same name but another uid
+        assert!(!is_event_for(&event(json!({"name": "web-0", "uid": "old"})), &pod));
+        assert!(is_event_for(&event(json!({"name": "web-0"})), &pod));
+        assert!(!is_event_for(&event(json!({"name": "web-1"})), &pod));
+    }
+    
+    #[test]
+    fn kdbg_errors_keep_their_messages_and_codes() {
+        let error: anyhow::Error = KdbgError::NoPodMatch { pattern: "api".into(), namespace: Some("prod".into()) }.into();
+        assert_eq!(error.to_string(), "No pods found matching 'api'");
+        assert_eq!(error_json(&error)["error"], "NoPodMatch");
+        assert_eq!(error_json(&error)["namespace"], "prod");
+        
+        let error: anyhow::Error = KdbgError::NoSelectorMatch { selector: "app=api".into(), namespace: None }.into();
+        assert_eq!(error.to_string(), "No pods found matching selector 'app=api'");
+        assert_eq!(error_json(&anyhow::anyhow!("boom"))["error"], "Error");
+    }
+    
+    fn plain_diff(a: &str, b: &str) -> Vec<String> {
+        with_colors(false, || unified_diff(a, b, "a", "b"))
+    }
+    
+    #[test]
+    fn unified_diff_is_empty_for_equal_inputs() {
+        assert!(plain_diff("x\ny\n", "x\ny\n").is_empty());
+        assert!(plain_diff("", "").is_empty());
+    }
+    
+    #[test]
+    fn unified_diff_keeps_three_lines_of_context() {
+        let a: String = (1..=10).map(|n| format!("{}\n", n)).collect();
+        let b = a.replace("5\n", "five\n");
+        assert_eq!(plain_diff(&a, &b), [
+            "--- a", "+++ b", "@@ -2,7 +2,7 @@", " 2", " 3", " 4", "-5", "+five", " 6", " 7", " 8",
+        ]);
+    }
+    
+    #[test]
+    fn unified_diff_splits_distant_changes_into_hunks() {
+        let a: String = (1..=20).map(|n| format!("{}\n", n)).collect();
+        let b = a.replace("\n2\n", "\ntwo\n").replace("\n19\n", "\nnineteen\n");
+        let diff = plain_diff(&a, &b);
+        let headers: Vec<&String> = diff.iter().filter(|line| line.starts_with("@@")).collect();
+        assert_eq!(headers, ["@@ -1,5 +1,5 @@", "@@ -16,5 +16,5 @@"]);
+        
+        // Changes 7 lines apart share their context and stay in one hunk
+        let b = a.replace("\n2\n", "\ntwo\n").replace("\n9\n", "\nnine\n");
+        let diff = plain_diff(&a, &b);
+        assert_eq!(diff.iter().filter(|line| line.starts_with("@@")).count(), 1);
+        assert_eq!(diff[2], "@@ -1,12 +1,12 @@");
+    }
+    
+    #[test]
+    fn unified_diff_handles_all_added_and_all_removed() {
+        assert_eq!(plain_diff("", "x\ny\n"), ["--- a", "+++ b", "@@ -0,0 +1,2 @@", "+x", "+y"]);
+        assert_eq!(plain_diff("x\ny\n", ""), ["--- a", "+++ b", "@@ -1,2 +0,0 @@", "-x", "-y"]);
+    }
+    
+    #[test]
+    fn parse_duration_handles_units() {
+        assert_eq!(parse_duration("45"), Ok(std::time::Duration::from_secs(45)));
+        assert_eq!(parse_duration("30s"), Ok(std::time::Duration::from_secs(30)));
+        assert_eq!(parse_duration("5m"), Ok(std::time::Duration::from_secs(300)));
+        assert_eq!(parse_duration("2h"), Ok(std::time::Duration::from_secs(7200)));
+        assert_eq!(parse_duration(" 1d "), Ok(std::time::Duration::from_secs(86400)));
+    }
+    
+    #[test]
+    fn parse_duration_rejects_bad_and_overflowing_values() {
+        for value in ["", "m", "5x", "1.5h", "-1s", "99999999999999999999"] {
+            assert!(parse_duration(value).is_err(), "{}", value);
+        }
+        assert!(parse_duration("999999999999999999d").unwrap_err().contains("too long"));
+        assert!(parse_duration(&format!("{}m", u64::MAX / 60 + 1)).is_err());
+        assert_eq!(parse_duration(&format!("{}s", u64::MAX)), Ok(std::time::Duration::from_secs(u64::MAX)));
+    }
+    
+    #[test]
+    fn validate_resource_name_accepts_dns_subdomains() {
+        for name in ["api", "api-7d4f8c9b5-xk2lp", "web-0", "my.app.v2"] {
+            assert!(validate_resource_name(name).is_ok(), "{}", name);
+        }
+    }
+    
+    #[test]
+    fn validate_resource_name_rejects_invalid_names() {
+        let too_long = "a".repeat(254);
+        for name in ["", "Api", "-api", "api-", "api pod", "api;id", "api,x=y", "it's", "$pod", too_long.as_str()] {
+            assert!(validate_resource_name(name).is_err(), "{}", name);
+        }
+    }
+}